@@ -0,0 +1,34 @@
+//! `aggregate` end to end: splitting between workers, parsing, merging and
+//! sorting.
+//!
+//! `cargo bench --bench aggregate`
+
+mod common;
+
+use std::thread;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+const SIZE: usize = 100 << 20;
+
+fn aggregate(c: &mut Criterion) {
+    let input = common::measurements(SIZE, "");
+    let cores = thread::available_parallelism().map_or(1, |n| n.get());
+
+    let mut group = c.benchmark_group("aggregate");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    let mut threads = vec![1];
+    threads.extend((cores > 1).then_some(cores));
+    for threads in threads {
+        group.bench_with_input(
+            BenchmarkId::new("threads", threads),
+            &threads,
+            |b, &threads| b.iter(|| brc_rust::aggregate(&input, threads)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, aggregate);
+criterion_main!(benches);
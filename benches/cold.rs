@@ -0,0 +1,57 @@
+//! `aggregate_file` on a cold page cache: the file's pages are evicted with
+//! `posix_fadvise` before every run, so the parse waits on the disk and the
+//! kernel's readahead. Only on Linux, other platforms lack the eviction.
+//!
+//! `cargo bench --bench cold`
+
+mod common;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+#[cfg(target_os = "linux")]
+fn cold(c: &mut Criterion) {
+    use std::{ffi::c_int, fs, os::fd::AsRawFd};
+
+    use criterion::{BatchSize, Throughput};
+
+    extern "C" {
+        fn posix_fadvise(fd: c_int, offset: i64, len: i64, advice: c_int) -> c_int;
+    }
+    const POSIX_FADV_DONTNEED: c_int = 4;
+
+    const SIZE: usize = 256 << 20;
+
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("cold.txt");
+    fs::write(&path, common::measurements(SIZE, "")).unwrap();
+    let path = path.to_str().unwrap();
+
+    let evict = || {
+        let file = fs::File::open(path).unwrap();
+        // Evicting needs the dirty pages of the write above written back
+        file.sync_all().unwrap();
+        assert_eq!(
+            unsafe { posix_fadvise(file.as_raw_fd(), 0, 0, POSIX_FADV_DONTNEED) },
+            0
+        );
+    };
+
+    let mut group = c.benchmark_group("aggregate_file");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(SIZE as u64));
+    // Only the parse is timed, not the eviction before it
+    group.bench_function("cold", |b| {
+        b.iter_batched(
+            evict,
+            |()| brc_rust::aggregate_file(path).unwrap(),
+            BatchSize::PerIteration,
+        )
+    });
+    group.finish();
+    fs::remove_file(path).unwrap();
+}
+
+#[cfg(not(target_os = "linux"))]
+fn cold(_c: &mut Criterion) {}
+
+criterion_group!(benches, cold);
+criterion_main!(benches);
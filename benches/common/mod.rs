@@ -0,0 +1,87 @@
+//! Synthetic measurements shared by the benchmarks.
+
+#![allow(dead_code)]
+
+/// Stations with their mean temperature in tenths, the spread of name lengths
+/// of the challenge's list
+const STATIONS: &[(&str, i32)] = &[
+    ("Abha", 180),
+    ("Abidjan", 260),
+    ("Accra", 264),
+    ("Addis Ababa", 160),
+    ("Adelaide", 173),
+    ("Alexandria", 200),
+    ("Anchorage", 28),
+    ("Baghdad", 227),
+    ("Bangkok", 286),
+    ("Bulawayo", 189),
+    ("Cabo San Lucas", 239),
+    ("Cape Town", 162),
+    ("Chihuahua", 186),
+    ("Dar es Salaam", 258),
+    ("Dhaka", 259),
+    ("Dikson", -111),
+    ("Dushanbe", 147),
+    ("Erzurum", 51),
+    ("Fianarantsoa", 179),
+    ("Guatemala City", 204),
+    ("Hamburg", 97),
+    ("Ho Chi Minh City", 274),
+    ("Hong Kong", 233),
+    ("Honolulu", 254),
+    ("Iqaluit", -93),
+    ("Jakarta", 267),
+    ("Kuala Lumpur", 273),
+    ("Las Palmas de Gran Canaria", 212),
+    ("Lhasa", 76),
+    ("Lodwar", 293),
+    ("Mexico City", 175),
+    ("Napier", 146),
+    ("Nouakchott", 257),
+    ("Oklahoma City", 159),
+    ("Ouagadougou", 283),
+    ("Palembang", 273),
+    ("Petropavlovsk-Kamchatsky", 19),
+    ("Reykjavík", 43),
+    ("Saint Petersburg", 58),
+    ("San José", 226),
+    ("São Paulo", 199),
+    ("St. John's", 50),
+    ("Thiès", 240),
+    ("Ulaanbaatar", -4),
+    ("Vladivostok", 49),
+    ("Wellington", 129),
+    ("Yellowknife", -43),
+    ("Zürich", 93),
+];
+
+/// Generates about `size` bytes of `station;value` lines: stations picked
+/// uniformly, values spread around each station's mean. `prefix` is put in
+/// front of every station name.
+pub fn measurements(size: usize, prefix: &str) -> Vec<u8> {
+    let mut state: u64 = 0x5eed;
+    let mut next = move || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 33) as i32
+    };
+
+    let mut input = Vec::with_capacity(size + 64);
+    while input.len() < size {
+        let (station, mean) = STATIONS[next() as usize % STATIONS.len()];
+        // The sum of four uniform draws, roughly normal with a spread of 10°
+        let noise = (0..4).map(|_| next() % 346 - 173).sum::<i32>();
+        let val = (mean + noise).clamp(-999, 999);
+
+        let sign = if val < 0 { "-" } else { "" };
+        let line = format!(
+            "{prefix}{station};{sign}{}.{}\n",
+            val.abs() / 10,
+            val.abs() % 10
+        );
+        input.extend_from_slice(line.as_bytes());
+    }
+
+    input
+}
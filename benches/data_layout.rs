@@ -0,0 +1,135 @@
+//! Updating a table of aggregates laid out like `Data`, 16 bytes with the
+//! extremes packed into `i16`s, against the 24 bytes `i32` extremes take next
+//! to an `i64` sum. The stations are spread over the `MAP_SIZE` slots by their
+//! hash, so the table spans far more cache lines than the stations touch.
+//!
+//! `cargo bench --bench data_layout`
+
+mod common;
+
+use std::hint::black_box;
+
+use brc_rust::{
+    hasher::{Djb2, StationHasher},
+    MAP_SIZE,
+};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+const SIZE: usize = 4 << 20;
+/// Copies of the stations, each behind a prefix of its own
+const PREFIXES: usize = 200;
+
+trait Aggregate: Copy {
+    const EMPTY: Self;
+
+    fn add(&mut self, val: i32);
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct Packed {
+    sum: i64,
+    count: u32,
+    min: i16,
+    max: i16,
+}
+
+impl Aggregate for Packed {
+    const EMPTY: Self = Packed {
+        sum: 0,
+        count: 0,
+        min: i16::MAX,
+        max: i16::MIN,
+    };
+
+    fn add(&mut self, val: i32) {
+        self.sum += val as i64;
+        self.count += 1;
+        self.min = self.min.min(val as i16);
+        self.max = self.max.max(val as i16);
+    }
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct Wide {
+    sum: i64,
+    count: u32,
+    min: i32,
+    max: i32,
+}
+
+impl Aggregate for Wide {
+    const EMPTY: Self = Wide {
+        sum: 0,
+        count: 0,
+        min: i32::MAX,
+        max: i32::MIN,
+    };
+
+    fn add(&mut self, val: i32) {
+        self.sum += val as i64;
+        self.count += 1;
+        self.min = self.min.min(val);
+        self.max = self.max.max(val);
+    }
+}
+
+/// The home slot and value of every line, interleaving the prefixes so
+/// consecutive lines land far apart in the table
+fn updates() -> Vec<(usize, i32)> {
+    let inputs = (0..PREFIXES)
+        .map(|idx| common::measurements(SIZE / PREFIXES, &format!("{idx} ")))
+        .collect::<Vec<_>>();
+    let mut lines = inputs
+        .iter()
+        .map(|input| {
+            input
+                .split(|&ch| ch == b'\n')
+                .filter(|line| !line.is_empty())
+        })
+        .collect::<Vec<_>>();
+
+    let mut updates = Vec::new();
+    loop {
+        let before = updates.len();
+        for line in lines.iter_mut().filter_map(Iterator::next) {
+            let delim = line.iter().rposition(|&ch| ch == b';').unwrap();
+            let (name, value) = (&line[..delim], &line[delim + 1..]);
+            let value = std::str::from_utf8(value).unwrap().replace('.', "");
+            updates.push((Djb2::hash(name) as usize % MAP_SIZE, value.parse().unwrap()));
+        }
+        if updates.len() == before {
+            return updates;
+        }
+    }
+}
+
+fn apply<T: Aggregate>(updates: &[(usize, i32)], table: &mut [T]) {
+    table.fill(T::EMPTY);
+    for &(slot, val) in updates {
+        table[slot].add(val);
+    }
+}
+
+fn data_layout(c: &mut Criterion) {
+    assert_eq!(size_of::<Packed>(), 16);
+    assert_eq!(size_of::<Wide>(), 24);
+
+    let updates = updates();
+    let mut packed = vec![Packed::EMPTY; MAP_SIZE];
+    let mut wide = vec![Wide::EMPTY; MAP_SIZE];
+
+    let mut group = c.benchmark_group("data layout");
+    group.throughput(Throughput::Elements(updates.len() as u64));
+    group.bench_function("packed 16 bytes", |b| {
+        b.iter(|| apply(black_box(&updates), &mut packed))
+    });
+    group.bench_function("wide 24 bytes", |b| {
+        b.iter(|| apply(black_box(&updates), &mut wide))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, data_layout);
+criterion_main!(benches);
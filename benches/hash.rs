@@ -0,0 +1,24 @@
+//! Aggregation throughput with ASCII station names, hashed a word at a time,
+//! against the same names behind a multibyte character, hashed byte by byte.
+//!
+//! `cargo bench --bench hash`
+
+mod common;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+const SIZE: usize = 40 << 20;
+
+fn hash(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hash");
+    group.sample_size(10);
+    for (label, prefix) in [("ascii", ""), ("multibyte", "é")] {
+        let input = common::measurements(SIZE, prefix);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_function(label, |b| b.iter(|| brc_rust::aggregate(&input, 1)));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, hash);
+criterion_main!(benches);
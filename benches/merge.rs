@@ -0,0 +1,36 @@
+//! Merging the workers' tables, with their slots at the allocator's alignment
+//! and aligned to cache lines as under `--align-tables`.
+//!
+//! `cargo bench --bench merge --features bench`
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const STATIONS: usize = 10_000;
+const LINES: usize = 1_000_000;
+const WORKERS: usize = 16;
+
+fn merge(c: &mut Criterion) {
+    // Many stations, so the merge walks tables of thousands of slots
+    let input = (0..LINES)
+        .map(|i| format!("Station {};{}.{}\n", i * 7919 % STATIONS, i % 100, i % 10))
+        .collect::<String>()
+        .into_bytes()
+        .leak();
+
+    let mut group = c.benchmark_group(format!("merge {WORKERS} tables"));
+    group.sample_size(20);
+    for aligned in [false, true] {
+        // Each iteration parses the tables anew, only their merge is timed
+        group.bench_function(BenchmarkId::new("aligned", aligned), |b| {
+            b.iter_custom(|iters| {
+                (0..iters)
+                    .map(|_| brc_rust::bench::merge(input, WORKERS, aligned))
+                    .sum()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, merge);
+criterion_main!(benches);
@@ -0,0 +1,40 @@
+//! The parser on its own: `parse_line` over a single line and `consume` over
+//! a large buffer on the calling thread.
+//!
+//! `cargo bench --features bench --bench parse`
+
+mod common;
+
+use std::hint::black_box;
+
+use brc_rust::bench;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// Size of the buffer `consume` parses
+const SIZE: usize = 100 << 20;
+
+fn parse_line(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_line");
+    for line in [&b"Abha;-5.2\n"[..], b"Las Palmas de Gran Canaria;27.5\n"] {
+        let line: &'static [u8] = line;
+        group.throughput(Throughput::Bytes(line.len() as u64));
+        group.bench_function(
+            BenchmarkId::from_parameter(format!("{}B", line.len())),
+            |b| b.iter(|| bench::parse_line(black_box(line), 0)),
+        );
+    }
+    group.finish();
+}
+
+fn consume(c: &mut Criterion) {
+    let input = common::measurements(SIZE, "").leak();
+
+    let mut group = c.benchmark_group("consume");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("100MiB", |b| b.iter(|| bench::consume(input)));
+    group.finish();
+}
+
+criterion_group!(benches, parse_line, consume);
+criterion_main!(benches);
@@ -0,0 +1,62 @@
+//! The scan for the delimiter ending each station name, scalar and with SSE2
+//! under the `simd` feature, over every line of the generated measurements.
+//! Both scans have to agree on the delimiter and the hash of every name.
+//!
+//! `cargo bench --features bench,simd --bench scan`
+
+mod common;
+
+use std::hint::black_box;
+
+use brc_rust::bench;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+/// Size of the generated measurements
+const SIZE: usize = 100 << 20;
+
+fn scan(c: &mut Criterion) {
+    let input = common::measurements(SIZE, "").leak();
+    let lines = std::iter::once(0)
+        .chain(
+            input
+                .iter()
+                .enumerate()
+                .filter(|&(_, &ch)| ch == b'\n')
+                .map(|(idx, _)| idx + 1),
+        )
+        .filter(|&start| start < input.len())
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("scan_name");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("scalar", |b| {
+        b.iter(|| {
+            for &start in &lines {
+                black_box(bench::scan_name(input, start));
+            }
+        })
+    });
+
+    #[cfg(all(feature = "simd", target_arch = "x86_64", target_feature = "sse2"))]
+    {
+        for &start in &lines {
+            assert_eq!(
+                bench::scan_name_sse2(input, start),
+                bench::scan_name(input, start),
+                "line at {start}"
+            );
+        }
+        group.bench_function("sse2", |b| {
+            b.iter(|| {
+                for &start in &lines {
+                    black_box(bench::scan_name_sse2(input, start));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, scan);
+criterion_main!(benches);
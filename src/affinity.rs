@@ -0,0 +1,41 @@
+//! Pinning of the merge thread for `--pin-merge`. The pinning goes through
+//! the [`Affinity`] trait, so what's set and restored around the merge can be
+//! observed without changing the process' real affinity.
+
+use crate::{logging, util};
+
+/// Pins the calling thread to a CPU. Dropping the returned guard restores the
+/// affinity the thread had before.
+pub trait Affinity {
+    type Guard;
+
+    /// Pins the calling thread to `cpu`, `None` if it can't be.
+    fn pin_current_thread(&self, cpu: usize) -> Option<Self::Guard>;
+}
+
+/// The operating system's affinity of the calling thread
+pub struct System;
+
+impl Affinity for System {
+    type Guard = util::AffinityGuard;
+
+    fn pin_current_thread(&self, cpu: usize) -> Option<Self::Guard> {
+        util::pin_current_thread(cpu)
+    }
+}
+
+/// Runs `merge` on the calling thread pinned to `cpu`, if any, so it isn't
+/// moved between CPUs while the workers' results are folded together. The
+/// previous affinity is restored once `merge` returns. A thread that can't be
+/// pinned runs `merge` as it is.
+pub fn pinned<A: Affinity, T>(affinity: &A, cpu: Option<usize>, merge: impl FnOnce() -> T) -> T {
+    let _guard = cpu.and_then(|cpu| {
+        let guard = affinity.pin_current_thread(cpu);
+        if guard.is_none() {
+            logging::warn!("failed to pin the merge thread to CPU {cpu}");
+        }
+        guard
+    });
+
+    merge()
+}
@@ -0,0 +1,77 @@
+//! Diagnostics about the shape of the input rather than its measurements.
+
+use std::{collections::BTreeMap, fmt};
+
+/// Distribution of record lengths in bytes, not counting the newline.
+#[derive(Debug, PartialEq)]
+pub struct LineLengths {
+    pub lines: u64,
+    pub min: usize,
+    pub max: usize,
+    pub mean: f64,
+    /// `(percentile, length)` pairs for [`PERCENTILES`]
+    pub percentiles: Vec<(f64, usize)>,
+}
+
+pub const PERCENTILES: [f64; 4] = [50., 90., 99., 99.9];
+
+impl LineLengths {
+    pub fn collect(data: &[u8]) -> Self {
+        // Line lengths cluster tightly, so counting per distinct length stays
+        // small even when an odd line is very long
+        let mut counts = BTreeMap::<usize, u64>::new();
+        let data = data.strip_suffix(b"\n").unwrap_or(data);
+        if !data.is_empty() {
+            for line in data.split(|&ch| ch == b'\n') {
+                *counts.entry(line.len()).or_default() += 1;
+            }
+        }
+
+        let lines = counts.values().sum::<u64>();
+        let total = counts.iter().map(|(len, n)| *len as u64 * n).sum::<u64>();
+        let percentiles = PERCENTILES
+            .iter()
+            .map(|&p| {
+                let rank = ((p / 100. * lines as f64).ceil() as u64).max(1);
+                let mut seen = 0;
+                let len = counts
+                    .iter()
+                    .find(|(_, n)| {
+                        seen += *n;
+                        seen >= rank
+                    })
+                    .map_or(0, |(len, _)| *len);
+
+                (p, len)
+            })
+            .collect();
+
+        LineLengths {
+            lines,
+            min: counts.keys().next().copied().unwrap_or(0),
+            max: counts.keys().next_back().copied().unwrap_or(0),
+            mean: if lines == 0 {
+                0.
+            } else {
+                total as f64 / lines as f64
+            },
+            percentiles,
+        }
+    }
+}
+
+impl fmt::Display for LineLengths {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "lines: {}", self.lines)?;
+        writeln!(
+            f,
+            "line length: min={} max={} mean={:.1}",
+            self.min, self.max, self.mean
+        )?;
+        for (p, len) in &self.percentiles {
+            writeln!(f, "line length p{p}: {len}")?;
+        }
+
+        Ok(())
+    }
+}
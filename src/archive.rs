@@ -0,0 +1,27 @@
+//! Inputs shipped inside a tar archive, for `--tar-member`.
+
+use std::{
+    fs,
+    io::{self, Read},
+    path::Path,
+};
+
+/// Reads the member `name` of the tar archive at `path` into memory. Members
+/// can't be mapped in place, and the buffer is leaked to live as long as a
+/// mapped file would.
+pub fn load_member(path: &str, name: &str) -> io::Result<&'static [u8]> {
+    let mut archive = tar::Archive::new(fs::File::open(path)?);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if entry.path()? == Path::new(name) {
+            let mut buf = Vec::with_capacity(entry.size() as usize);
+            entry.read_to_end(&mut buf)?;
+            return Ok(buf.leak());
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("no member {name} in the archive"),
+    ))
+}
@@ -0,0 +1,135 @@
+//! Entry points into the parser and the lookup table for the benchmarks in
+//! `benches/` and the tests, behind the `bench` feature. Not a stable API.
+
+use std::{
+    mem::align_of,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    chunk_bounds, hash_name, read_header, Data, LookupTable, Monitors, Options, Slot, StationStats,
+};
+
+/// Parses the line at `offset`, returning the offset of its newline.
+#[inline]
+pub fn parse_line(data: &'static [u8], offset: usize) -> Option<usize> {
+    crate::parse_line::<crate::Djb2>(data, offset, b';').map(|parsed| parsed.next)
+}
+
+/// Offset of the delimiter ending the name at `offset` and the name's hash,
+/// as scanned without the `simd` feature.
+pub fn scan_name(data: &[u8], offset: usize) -> (usize, u32) {
+    crate::hasher::scan_name(data, offset, b';')
+}
+
+/// `scan_name` with SSE2, as with the `simd` feature.
+#[cfg(all(feature = "simd", target_arch = "x86_64", target_feature = "sse2"))]
+pub fn scan_name_sse2(data: &[u8], offset: usize) -> (usize, u32) {
+    crate::hasher::scan_name_sse2(data, offset, b';')
+}
+
+/// Aggregates all of `data` on the calling thread, returning the number of
+/// stations.
+pub fn consume(data: &'static [u8]) -> usize {
+    let opts = Options::default();
+    let header = read_header(data);
+    let mut store = LookupTable::new();
+    let monitors = Monitors {
+        progress: None,
+        live: None,
+    };
+    crate::consume(
+        data,
+        header.len,
+        data.len() - header.len,
+        &mut store,
+        &opts,
+        &header,
+        monitors,
+    );
+
+    store.into_iter().count()
+}
+
+/// Splits `data` between `workers` tables as the workers would, aligned to
+/// cache lines if `aligned`, then merges them on the calling thread. Returns
+/// how long the merge alone took.
+pub fn merge(data: &'static [u8], workers: usize, aligned: bool) -> Duration {
+    let opts = Options {
+        align_tables: aligned,
+        ..Options::default()
+    };
+    let header = read_header(data);
+    let monitors = Monitors {
+        progress: None,
+        live: None,
+    };
+    let stores = chunk_bounds(&data[header.len..], workers)
+        .into_iter()
+        .map(|(start, end)| {
+            let mut store = LookupTable::for_worker(&opts);
+            let start = header.len + start;
+            crate::consume(
+                data,
+                start,
+                end - start,
+                &mut store,
+                &opts,
+                &header,
+                monitors,
+            );
+            store
+        })
+        .collect::<Vec<_>>();
+
+    let started = Instant::now();
+    let mut store = LookupTable::new();
+    for local_store in stores {
+        store.merge_into(local_store);
+    }
+    let elapsed = started.elapsed();
+    assert!(store.into_iter().count() > 0);
+
+    elapsed
+}
+
+/// A `LookupTable` of a chosen size, to observe where its stations land.
+pub struct Table(LookupTable);
+
+impl Table {
+    /// A table of `slots` slots, growing past its load factor like the
+    /// workers' tables do.
+    pub fn with_slots(slots: usize) -> Self {
+        Table(LookupTable::with_slots(slots, align_of::<Slot>()))
+    }
+
+    /// Number of slots
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Adds `val`, in tenths of a degree, to the aggregate `LookupTable::entry`
+    /// returns for `name`. Returns the slot the aggregate is in and its count
+    /// before `val` was added.
+    pub fn add(&mut self, name: &'static [u8], val: i16) -> (usize, u32) {
+        let hash = hash_name(name);
+        let data = self.0.entry(name, hash);
+        let count = data.count;
+        data.merge(&Data {
+            min: val,
+            max: val,
+            sum: val as i64,
+            count: 1,
+        });
+
+        (self.0.find_slot(name, hash), count)
+    }
+
+    /// The stations in slot order, each with the slot it's in.
+    pub fn stations(self) -> Vec<(&'static [u8], StationStats, usize)> {
+        self.0
+            .into_iter()
+            .map(|(name, data, slot)| (name, data.stats(), slot as usize))
+            .collect()
+    }
+}
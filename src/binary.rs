@@ -0,0 +1,106 @@
+//! Fixed-width binary input, for `--input-format binary`, which skips parsing
+//! text altogether.
+//!
+//! ```text
+//! magic    b"BRCB"
+//! names    u16 count, then per name a u8 length and its bytes; a name's id
+//!          is its position in this list
+//! records  u16 name id and i16 value in tenths until the end of the file
+//! ```
+//!
+//! All integers are little endian.
+
+use std::{io, thread};
+
+use crate::{
+    hash_name, options::Options, recommended_threads, record, requested_threads, worker_builder,
+    LookupTable, ParseResult,
+};
+
+const MAGIC: &[u8] = b"BRCB";
+const RECORD_SIZE: usize = 4;
+
+/// A station name along with its hash
+type Name = (&'static [u8], u32);
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+/// Splits `data` into the names and the records.
+fn read_header(data: &'static [u8]) -> io::Result<(Vec<Name>, &'static [u8])> {
+    let truncated = || invalid("truncated header");
+
+    let rest = data
+        .strip_prefix(MAGIC)
+        .ok_or_else(|| invalid("not a binary measurements file"))?;
+    let (count, mut rest) = rest.split_first_chunk::<2>().ok_or_else(truncated)?;
+
+    let count = u16::from_le_bytes(*count);
+    let mut names = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let (&len, tail) = rest.split_first().ok_or_else(truncated)?;
+        let name = tail.get(..len as usize).ok_or_else(truncated)?;
+        names.push((name, hash_name(name)));
+        rest = &tail[len as usize..];
+    }
+
+    Ok((names, rest))
+}
+
+/// Aggregates the records of `data` into `store`, split evenly between the
+/// workers.
+pub fn aggregate(data: &'static [u8], store: &mut LookupTable, opts: &Options) -> io::Result<()> {
+    let (names, records) = read_header(data)?;
+    if records.len() % RECORD_SIZE != 0 {
+        return Err(invalid("truncated record"));
+    }
+
+    let count = records.len() / RECORD_SIZE;
+    let workers = requested_threads(opts)
+        .unwrap_or_else(|| recommended_threads(records.len() as u64))
+        .min(count)
+        .max(1);
+    let chunk_size = count.div_ceil(workers).max(1) * RECORD_SIZE;
+
+    thread::scope(|s| {
+        let names = &names;
+        let handles = records
+            .chunks(chunk_size)
+            .map(|chunk| {
+                worker_builder(opts)
+                    .spawn_scoped(s, move || aggregate_chunk(chunk, names))
+                    .expect("failed to spawn worker")
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            store.merge_into(handle.join().unwrap()?);
+        }
+
+        Ok(())
+    })
+}
+
+fn aggregate_chunk(records: &[u8], names: &[(&'static [u8], u32)]) -> io::Result<LookupTable> {
+    let mut store = LookupTable::new_with_capacity(names.len());
+    for bytes in records.chunks_exact(RECORD_SIZE) {
+        let id = u16::from_le_bytes([bytes[0], bytes[1]]);
+        let val = i16::from_le_bytes([bytes[2], bytes[3]]);
+        let &(place, place_hash) = names
+            .get(id as usize)
+            .ok_or_else(|| invalid(format!("unknown name id {id}")))?;
+
+        let parsed = ParseResult {
+            place,
+            place_hash,
+            val: val as i32,
+            has_value: true,
+            ts: &[],
+            next: 0,
+        };
+        record(&mut store, &parsed);
+    }
+
+    Ok(store)
+}
@@ -0,0 +1,59 @@
+//! Comparison of the output against a reference file, for `--check`.
+//!
+//! The text format is compared station by station, its `, ` separated
+//! entries between the braces, other formats line by line. Only the first
+//! difference is reported.
+
+use std::borrow::Cow;
+
+/// The entries of `output`, a station each in the text format.
+fn entries(output: &[u8]) -> Vec<&[u8]> {
+    let output = output.strip_suffix(b"\n").unwrap_or(output);
+    match output.strip_prefix(b"{").and_then(|o| o.strip_suffix(b"}")) {
+        Some(b"") => Vec::new(),
+        Some(stations) => split(stations, b", "),
+        None => split(output, b"\n"),
+    }
+}
+
+fn split<'a>(mut data: &'a [u8], sep: &[u8]) -> Vec<&'a [u8]> {
+    let mut parts = Vec::new();
+    while let Some(pos) = data.windows(sep.len()).position(|window| window == sep) {
+        parts.push(&data[..pos]);
+        data = &data[pos + sep.len()..];
+    }
+    parts.push(data);
+
+    parts
+}
+
+/// Checks `actual` matches `expected`, otherwise describes the first entry
+/// that differs.
+pub fn compare(expected: &[u8], actual: &[u8]) -> Result<(), String> {
+    if expected == actual {
+        return Ok(());
+    }
+
+    let (expected, actual) = (entries(expected), entries(actual));
+    let idx = (0..)
+        .find(|&idx| expected.get(idx) != actual.get(idx))
+        .unwrap();
+    // Entries alike but for the framing, e.g. a trailing newline
+    if idx >= expected.len().max(actual.len()) {
+        return Err("output differs from the reference in its framing".to_string());
+    }
+
+    Err(format!(
+        "output differs from the reference at entry {}\n- {}\n+ {}",
+        idx + 1,
+        show(expected.get(idx).copied()),
+        show(actual.get(idx).copied())
+    ))
+}
+
+fn show(entry: Option<&[u8]>) -> Cow<'_, str> {
+    match entry {
+        Some(entry) => String::from_utf8_lossy(entry),
+        None => Cow::Borrowed("(nothing)"),
+    }
+}
@@ -0,0 +1,115 @@
+//! Flat binary result file meant to be mmapped and binary-searched by other
+//! processes.
+//!
+//! Layout (all integers little-endian):
+//! - header: magic `BRCF`, `u32` version, `u64` record count
+//! - records sorted by name, each [`RECORD_SIZE`] bytes: `u32` name hash,
+//!   `u32` name offset, `u32` name length, `u32` count, `i32` min, `i32` max,
+//!   `i64` sum (values in tenths of a degree)
+//! - names blob, record name offsets are relative to its start
+
+use std::io::{self, Write};
+
+use crate::{
+    hash_name,
+    sink::{ResultSink, StationResult},
+    Data,
+};
+
+const MAGIC: &[u8; 4] = b"BRCF";
+const VERSION: u32 = 1;
+const HEADER_SIZE: usize = 16;
+const RECORD_SIZE: usize = 32;
+
+/// Writes the flat file, the names blob is held back until `finish` as it
+/// follows all the records.
+pub struct FlatSink<W: Write> {
+    w: W,
+    names: Vec<u8>,
+}
+
+impl<W: Write> FlatSink<W> {
+    pub fn new(w: W) -> Self {
+        FlatSink {
+            w,
+            names: Vec::new(),
+        }
+    }
+}
+
+impl<W: Write> ResultSink for FlatSink<W> {
+    fn begin(&mut self, stations: usize) -> io::Result<()> {
+        self.w.write_all(MAGIC)?;
+        self.w.write_all(&VERSION.to_le_bytes())?;
+        self.w.write_all(&(stations as u64).to_le_bytes())
+    }
+
+    fn write_station(&mut self, station: &StationResult) -> io::Result<()> {
+        let (name, data) = (station.name, &station.data);
+
+        let mut record = [0u8; RECORD_SIZE];
+        record[0..4].copy_from_slice(&hash_name(name).to_le_bytes());
+        record[4..8].copy_from_slice(&(self.names.len() as u32).to_le_bytes());
+        record[8..12].copy_from_slice(&(name.len() as u32).to_le_bytes());
+        record[12..16].copy_from_slice(&data.count.to_le_bytes());
+        record[16..20].copy_from_slice(&(data.min as i32).to_le_bytes());
+        record[20..24].copy_from_slice(&(data.max as i32).to_le_bytes());
+        record[24..32].copy_from_slice(&data.sum.to_le_bytes());
+        self.names.extend_from_slice(name);
+
+        self.w.write_all(&record)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.w.write_all(&self.names)?;
+        self.w.flush()
+    }
+}
+
+/// Looks up `name` in a buffer written by [`FlatSink`], returning `None` if
+/// the station is absent or the buffer isn't a flat result file.
+pub fn lookup(buf: &[u8], name: &[u8]) -> Option<Data> {
+    if buf.len() < HEADER_SIZE || &buf[0..4] != MAGIC || read_u32(buf, 4) != VERSION {
+        return None;
+    }
+
+    // A corrupt count mustn't wrap around to a small names offset
+    let count = usize::try_from(u64::from_le_bytes(buf[8..16].try_into().unwrap())).ok()?;
+    let names_offset = count
+        .checked_mul(RECORD_SIZE)
+        .and_then(|records| records.checked_add(HEADER_SIZE))?;
+    let names = buf.get(names_offset..)?;
+    let record = |idx: usize| {
+        let offset = HEADER_SIZE + idx * RECORD_SIZE;
+        buf.get(offset..offset + RECORD_SIZE)
+    };
+    let record_name = |record: &[u8]| {
+        let offset = read_u32(record, 4) as usize;
+        names.get(offset..offset.checked_add(read_u32(record, 8) as usize)?)
+    };
+
+    let (mut lo, mut hi) = (0, count);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        let rec = record(mid)?;
+
+        match record_name(rec)?.cmp(name) {
+            std::cmp::Ordering::Less => lo = mid + 1,
+            std::cmp::Ordering::Greater => hi = mid,
+            std::cmp::Ordering::Equal => {
+                return Some(Data {
+                    count: read_u32(rec, 12),
+                    min: read_u32(rec, 16) as i16,
+                    max: read_u32(rec, 20) as i16,
+                    sum: i64::from_le_bytes(rec[24..32].try_into().unwrap()),
+                })
+            }
+        }
+    }
+
+    None
+}
+
+fn read_u32(buf: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(buf[offset..offset + 4].try_into().unwrap())
+}
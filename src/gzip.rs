@@ -0,0 +1,43 @@
+//! Gzip compression of the output, for an `--output` path ending in `.gz`,
+//! and decompression of gzipped inputs, with `flate2` behind the `gzip`
+//! feature.
+
+use std::io;
+
+#[cfg(feature = "gzip")]
+use std::io::{Read, Write};
+
+#[cfg(feature = "gzip")]
+use flate2::{read::MultiGzDecoder, write::GzEncoder, Compression};
+
+const MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Compresses everything written to it into a gzip stream on `w`, complete
+/// once the encoder is dropped.
+#[cfg(feature = "gzip")]
+pub fn encoder<W: Write>(w: W) -> GzEncoder<W> {
+    GzEncoder::new(w, Compression::default())
+}
+
+/// Whether `data` starts like a gzip stream.
+pub fn is_gzip(data: &[u8]) -> bool {
+    data.starts_with(&MAGIC)
+}
+
+/// Decompresses the gzip stream in `data`, all of its members when several
+/// were concatenated, checking each one's CRC and size.
+#[cfg(feature = "gzip")]
+pub fn decompress(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len() * 4);
+    MultiGzDecoder::new(data).read_to_end(&mut out)?;
+
+    Ok(out)
+}
+
+#[cfg(not(feature = "gzip"))]
+pub fn decompress(_data: &[u8]) -> io::Result<Vec<u8>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "gzipped input requires building with the `gzip` feature",
+    ))
+}
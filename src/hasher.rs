@@ -0,0 +1,219 @@
+//! Hashing of station names into the slots of a `LookupTable`. The table and
+//! `parse_line` are generic over the `StationHasher`, so the collisions of
+//! other hash functions can be compared on real station sets.
+
+/// Hash of a station name, whose remainder by the table size is its home
+/// slot.
+pub trait StationHasher {
+    fn hash(bytes: &[u8]) -> u32;
+
+    /// Offset of the first `delim` from `offset`, or the end of `data` without
+    /// one, along with the hash of the bytes before it. Hashers able to hash
+    /// while looking for the delimiter can do both in one pass.
+    #[doc(hidden)]
+    #[inline(always)]
+    fn scan(data: &[u8], offset: usize, delim: u8) -> (usize, u32) {
+        let end = data[offset..]
+            .iter()
+            .position(|&ch| ch == delim)
+            .map_or(data.len(), |len| offset + len);
+
+        (end, Self::hash(&data[offset..end]))
+    }
+}
+
+/// djb2's seed with `hash * 65599 + ch` steps, the hash the stations are
+/// aggregated with. ASCII names are hashed a word at a time while looking for
+/// the delimiter.
+pub struct Djb2;
+
+impl StationHasher for Djb2 {
+    #[inline(always)]
+    fn hash(bytes: &[u8]) -> u32 {
+        hash_name(bytes)
+    }
+
+    #[inline(always)]
+    fn scan(data: &[u8], offset: usize, delim: u8) -> (usize, u32) {
+        #[cfg(all(feature = "simd", target_arch = "x86_64", target_feature = "sse2"))]
+        return scan_name_sse2(data, offset, delim);
+        #[cfg(not(all(feature = "simd", target_arch = "x86_64", target_feature = "sse2")))]
+        return scan_name(data, offset, delim);
+    }
+}
+
+/// The 32 bit FxHash of rustc: 4 bytes at a time, then the remaining ones.
+pub struct Fx;
+
+impl StationHasher for Fx {
+    fn hash(bytes: &[u8]) -> u32 {
+        const SEED: u32 = 0x9e3779b9;
+        let step = |hash: u32, word: u32| (hash.rotate_left(5) ^ word).wrapping_mul(SEED);
+
+        let mut words = bytes.chunks_exact(4);
+        let hash = (&mut words).fold(0, |hash, word| {
+            step(hash, u32::from_le_bytes(word.try_into().unwrap()))
+        });
+        words
+            .remainder()
+            .iter()
+            .fold(hash, |hash, &ch| step(hash, ch as u32))
+    }
+}
+
+/// Offset of the first `delim` from `offset`, or the end of `data` without
+/// one, along with the hash of the bytes before it. Hashes a word at a time
+/// while the name is ASCII, then byte by byte.
+#[inline(always)]
+pub(crate) fn scan_name(data: &[u8], offset: usize, delim: u8) -> (usize, u32) {
+    let mut hash = 5381;
+    let mut idx = offset;
+
+    // A word holding the delimiter leaves `idx` at it
+    while let Some((word, len)) = ascii_name_word(data, idx, delim) {
+        hash = hash_word(hash, word, len);
+        idx += len;
+        if len < 8 {
+            return (idx, hash);
+        }
+    }
+    while idx < data.len() {
+        let ch = unsafe { *data.get_unchecked(idx) };
+        if ch == delim {
+            break;
+        }
+
+        hash = hash_step(hash, ch);
+        idx += 1;
+    }
+
+    (idx, hash)
+}
+
+/// `scan_name` with the `simd` feature: finds the delimiter comparing 16 bytes
+/// at a time with SSE2 first, then hashes the name before it a word at a
+/// time. Falls back to `scan_name` near the end of `data`.
+#[cfg(all(feature = "simd", target_arch = "x86_64", target_feature = "sse2"))]
+#[inline(always)]
+pub(crate) fn scan_name_sse2(data: &[u8], offset: usize, delim: u8) -> (usize, u32) {
+    use std::arch::x86_64::{_mm_cmpeq_epi8, _mm_loadu_si128, _mm_movemask_epi8, _mm_set1_epi8};
+
+    let mut end = offset;
+    loop {
+        // Names running into the last 16 bytes are left to the scalar scan
+        if data.len() - end < 16 {
+            return scan_name(data, offset, delim);
+        }
+
+        // SAFETY: the target has SSE2 and the 16 bytes loaded are in `data`
+        let found = unsafe {
+            let chunk = _mm_loadu_si128(data.as_ptr().add(end).cast());
+            _mm_movemask_epi8(_mm_cmpeq_epi8(chunk, _mm_set1_epi8(delim as i8)))
+        };
+        if found != 0 {
+            end += found.trailing_zeros() as usize;
+            break;
+        }
+        end += 16;
+    }
+
+    let mut hash = 5381;
+    let mut idx = offset;
+    while idx < end {
+        let len = (end - idx).min(8);
+        let word = match data[idx..].first_chunk::<8>() {
+            Some(word) => u64::from_le_bytes(*word),
+            None => {
+                let mut word = [0; 8];
+                word[..len].copy_from_slice(&data[idx..end]);
+                u64::from_le_bytes(word)
+            }
+        };
+        hash = hash_word(hash, word & (u64::MAX >> (64 - 8 * len)), len);
+        idx += len;
+    }
+
+    (end, hash)
+}
+
+#[inline(always)]
+fn hash_step(hash: u32, ch: u8) -> u32 {
+    (ch as u32)
+        .wrapping_add(hash << 6)
+        .wrapping_add(hash << 16)
+        .wrapping_sub(hash)
+}
+
+/// The hash of `len` `hash_step`s over the bytes of `word`, lowest first,
+/// the others being 0. As `hash_step` is `hash * 65599 + ch`, that's a sum of
+/// products with powers of 65599, which unlike the steps don't wait on each
+/// other.
+#[inline(always)]
+fn hash_word(hash: u32, word: u64, len: usize) -> u32 {
+    const POWERS: [u32; 9] = {
+        let mut powers = [1u32; 9];
+        let mut idx = 1;
+        while idx < 9 {
+            powers[idx] = powers[idx - 1].wrapping_mul(65599);
+            idx += 1;
+        }
+        powers
+    };
+    // 65599 is odd and so are its powers, which makes them invertible modulo
+    // 2^32 (by Newton's iteration, each step doubling the correct bits)
+    const INVERSE_POWERS: [u32; 9] = {
+        let mut inverses = [1u32; 9];
+        let mut idx = 1;
+        while idx < 9 {
+            let power = POWERS[idx];
+            let mut inverse = power;
+            let mut step = 0;
+            while step < 5 {
+                inverse = inverse.wrapping_mul(2u32.wrapping_sub(power.wrapping_mul(inverse)));
+                step += 1;
+            }
+            inverses[idx] = inverse;
+            idx += 1;
+        }
+        inverses
+    };
+
+    // Weighting all 8 bytes as if the word were full leaves the `len` actual
+    // ones `8 - len` powers too high
+    let bytes = word.to_le_bytes();
+    let mut sum = 0u32;
+    for (idx, &ch) in bytes.iter().enumerate() {
+        sum = sum.wrapping_add((ch as u32).wrapping_mul(POWERS[7 - idx]));
+    }
+
+    hash.wrapping_mul(POWERS[len])
+        .wrapping_add(sum.wrapping_mul(INVERSE_POWERS[8 - len]))
+}
+
+/// The 8 bytes at `idx` as a little endian word, if they're all ASCII, along
+/// with the number of them before `delim`. The bytes from `delim` on are
+/// cleared.
+#[inline(always)]
+fn ascii_name_word(data: &[u8], idx: usize, delim: u8) -> Option<(u64, usize)> {
+    const LOW_BITS: u64 = u64::from_ne_bytes([0x01; 8]);
+    const HIGH_BITS: u64 = u64::from_ne_bytes([0x80; 8]);
+
+    let word = u64::from_le_bytes(*data.get(idx..)?.first_chunk::<8>()?);
+    if word & HIGH_BITS != 0 || !delim.is_ascii() {
+        return None;
+    }
+    // Bytes below 0x80 only wrap into their top bit when decremented if
+    // they're 0, or borrowed from by a 0 below them, so the lowest flagged
+    // byte is the first delimiter
+    let delims = (word ^ (LOW_BITS * delim as u64)).wrapping_sub(LOW_BITS) & HIGH_BITS;
+    let len = delims.trailing_zeros() / 8;
+    let kept = u64::MAX.checked_shr(64 - 8 * len).unwrap_or(0);
+
+    Some((word & kept, len as usize))
+}
+
+/// `Djb2::hash`, the hash of the names in the tables outside of
+/// `parse_line` and in the `flat` format.
+pub(crate) fn hash_name(name: &[u8]) -> u32 {
+    name.iter().fold(5381, |hash, &ch| hash_step(hash, ch))
+}
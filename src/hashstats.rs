@@ -0,0 +1,120 @@
+use std::{collections::HashSet, fmt};
+
+use crate::{hash_name, hasher::StationHasher, is_prime, LookupTable, MAP_SIZE};
+
+/// Slot occupancy of a station set laid out in a table using the hash from
+/// `parse_line` and linear probing.
+#[derive(Debug, PartialEq)]
+pub struct HashStats {
+    pub distinct: usize,
+    pub collisions: usize,
+    pub max_probe: usize,
+    pub load_factor: f64,
+}
+
+impl fmt::Display for HashStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "distinct={} collisions={} max_probe={} load_factor={:.3}",
+            self.distinct, self.collisions, self.max_probe, self.load_factor
+        )
+    }
+}
+
+/// Hash statistics of the stations in `table` as they were placed: a station
+/// away from its home slot collided, the distance it was probed to is its
+/// probe length.
+pub(crate) fn of_table<H: StationHasher>(table: &LookupTable<H>) -> HashStats {
+    let size = table.capacity();
+    let mut stats = HashStats {
+        distinct: table.len,
+        collisions: 0,
+        max_probe: 0,
+        load_factor: table.len as f64 / size as f64,
+    };
+
+    for (idx, slot) in table.slots.iter().enumerate() {
+        let Some((name, _)) = slot else { continue };
+        let home = H::hash(name) as usize % size;
+        let probe = (idx + size - home) % size;
+        if probe > 0 {
+            stats.collisions += 1;
+            stats.max_probe = stats.max_probe.max(probe);
+        }
+    }
+
+    stats
+}
+
+/// Candidate table sizes for `--tune`: the primes closest below and above
+/// each power of two up to 2^16, along with the current `MAP_SIZE`.
+fn candidate_sizes() -> Vec<usize> {
+    let mut sizes = vec![MAP_SIZE];
+    for shift in 8..=16 {
+        let power = 1usize << shift;
+        sizes.extend((2..power).rev().find(|&n| is_prime(n)));
+        sizes.extend((power..).find(|&n| is_prime(n)));
+    }
+    sizes.sort_unstable();
+    sizes.dedup();
+
+    sizes
+}
+
+/// Computes the hash statistics of the station set for every candidate table
+/// size that fits it, ordered from the best size to the worst: fewest
+/// collisions, then shortest probes, then smallest table.
+pub fn tune<'a>(inputs: impl IntoIterator<Item = &'a [u8]>, delim: u8) -> Vec<(usize, HashStats)> {
+    let names = station_names(inputs, delim);
+
+    let mut results = candidate_sizes()
+        .into_iter()
+        .filter(|&size| size >= names.len())
+        .map(|size| (size, compute(&names, size)))
+        .collect::<Vec<_>>();
+    results.sort_by_key(|(size, stats)| (stats.collisions, stats.max_probe, *size));
+
+    results
+}
+
+/// The distinct station names of the inputs, in the order they're first seen
+/// as that's the order they're placed in a table.
+fn station_names<'a>(inputs: impl IntoIterator<Item = &'a [u8]>, delim: u8) -> Vec<&'a [u8]> {
+    let mut seen = HashSet::new();
+    inputs
+        .into_iter()
+        .flat_map(|data| {
+            data.split(|&ch| ch == b'\n')
+                .filter_map(|line| line.iter().position(|&ch| ch == delim).map(|d| &line[..d]))
+        })
+        .filter(|name| seen.insert(*name))
+        .collect()
+}
+
+fn compute(names: &[&[u8]], size: usize) -> HashStats {
+    let mut occupied = vec![false; size];
+    let mut collisions = 0;
+    let mut max_probe = 0;
+
+    for name in names.iter() {
+        let home = hash_name(name) as usize % size;
+        if occupied[home] {
+            collisions += 1;
+        }
+
+        // A full table leaves the remaining names without a slot, they are
+        // still reported as collisions.
+        if let Some(probe) = (0..size).find(|p| !occupied[(home + p) % size]) {
+            occupied[(home + probe) % size] = true;
+            max_probe = max_probe.max(probe);
+        }
+    }
+
+    HashStats {
+        distinct: names.len(),
+        collisions,
+        max_probe,
+        load_factor: names.len() as f64 / size as f64,
+    }
+}
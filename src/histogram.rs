@@ -0,0 +1,53 @@
+//! Counts of each value a station measured, for `--percentiles`, which unlike
+//! min/mean/max can't be kept as running aggregates.
+
+/// Lowest value in tenths with a bucket of its own, the challenge's -99.9
+const LOWEST: i32 = -999;
+
+/// A bucket per tenth from -99.9 to 99.9
+const BUCKETS: usize = 1999;
+
+/// Values beyond the challenge's range are counted in the bucket at the edge
+/// they're past, so percentiles falling among them read -99.9 or 99.9.
+pub struct Histogram {
+    counts: Box<[u32; BUCKETS]>,
+    total: u64,
+}
+
+impl Histogram {
+    pub fn new() -> Self {
+        Histogram {
+            counts: Box::new([0; BUCKETS]),
+            total: 0,
+        }
+    }
+
+    pub fn record(&mut self, val: i32) {
+        let bucket = (val - LOWEST).clamp(0, BUCKETS as i32 - 1);
+        self.counts[bucket as usize] += 1;
+        self.total += 1;
+    }
+
+    pub fn merge(&mut self, other: &Histogram) {
+        for (count, other) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *count += other;
+        }
+        self.total += other.total;
+    }
+
+    /// The nearest rank `percentile`, in tenths: the smallest value at least
+    /// `percentile` percent of the values are at or below.
+    pub fn percentile(&self, percentile: f64) -> i32 {
+        let rank = ((percentile / 100. * self.total as f64).ceil() as u64).max(1);
+
+        let mut seen = 0;
+        for (bucket, &count) in self.counts.iter().enumerate() {
+            seen += count as u64;
+            if seen >= rank {
+                return bucket as i32 + LOWEST;
+            }
+        }
+
+        BUCKETS as i32 - 1 + LOWEST
+    }
+}
@@ -0,0 +1,29 @@
+//! Index of where each station's lines are in the input, for tools that want
+//! to re-read a station's raw lines.
+//!
+//! One `station;offset,offset,...` line per station sorted by name, offsets
+//! are ascending byte offsets of the starts of the lines.
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+pub fn write<W: Write>(mut w: W, offsets: HashMap<&[u8], Vec<usize>>) -> io::Result<()> {
+    let mut stations = offsets.into_iter().collect::<Vec<_>>();
+    stations.sort_unstable_by_key(|(name, _)| *name);
+
+    for (name, mut offsets) in stations {
+        // Workers finish in any order
+        offsets.sort_unstable();
+
+        w.write_all(name)?;
+        for (idx, offset) in offsets.iter().enumerate() {
+            let sep = if idx == 0 { ';' } else { ',' };
+            write!(w, "{sep}{offset}")?;
+        }
+        writeln!(w)?;
+    }
+
+    w.flush()
+}
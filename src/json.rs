@@ -0,0 +1,88 @@
+//! JSON array of the results for downstream tooling, one object per station:
+//!
+//! ```json
+//! [
+//!   {"station": "Abha", "min": -5.2, "mean": 18.0, "max": 40.1, "count": 12}
+//! ]
+//! ```
+//!
+//! Values have a single decimal like the text format, seeded stations without
+//! values have `null` extremes and mean.
+
+use std::io::{self, Write};
+
+use crate::{
+    decode_name, fmt_tenths,
+    options::Options,
+    sink::{fmt_mean, ResultSink, StationResult},
+};
+
+pub struct JsonSink<'a, W: Write> {
+    w: W,
+    opts: &'a Options,
+    first: bool,
+}
+
+impl<'a, W: Write> JsonSink<'a, W> {
+    pub fn new(w: W, opts: &'a Options) -> Self {
+        JsonSink {
+            w,
+            opts,
+            first: true,
+        }
+    }
+}
+
+impl<W: Write> ResultSink for JsonSink<'_, W> {
+    fn begin(&mut self, _stations: usize) -> io::Result<()> {
+        write!(self.w, "[")
+    }
+
+    fn write_station(&mut self, station: &StationResult) -> io::Result<()> {
+        let data = &station.data;
+        if !std::mem::take(&mut self.first) {
+            write!(self.w, ",")?;
+        }
+
+        let (min, mean, max) = match data.count {
+            0 => ("null".to_string(), "null".to_string(), "null".to_string()),
+            _ => (
+                fmt_tenths(data.min as i64),
+                fmt_mean(data, self.opts.rounding),
+                fmt_tenths(data.max as i64),
+            ),
+        };
+        write!(
+            self.w,
+            "\n  {{\"station\": \"{}\", \"min\": {min}, \"mean\": {mean}, \"max\": {max}, \"count\": {}}}",
+            escape(&decode_name(station.name, &self.opts.encoding)),
+            data.count
+        )
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if !self.first {
+            writeln!(self.w)?;
+        }
+        writeln!(self.w, "]")?;
+        self.w.flush()
+    }
+}
+
+/// Escapes a string's contents: quotes, backslashes and control characters.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '"' => escaped += "\\\"",
+            '\\' => escaped += "\\\\",
+            '\n' => escaped += "\\n",
+            '\r' => escaped += "\\r",
+            '\t' => escaped += "\\t",
+            ch if (ch as u32) < 0x20 => escaped += &format!("\\u{:04x}", ch as u32),
+            ch => escaped.push(ch),
+        }
+    }
+
+    escaped
+}
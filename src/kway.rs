@@ -0,0 +1,57 @@
+//! Ordering the results without a global sort, for `--kway-merge`: each
+//! worker sorts its own table and the sorted runs are merged by always taking
+//! the smallest name at their heads.
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::{sort, Data, LookupTable};
+
+/// A worker's stations, sorted by name
+pub type Run = Vec<(&'static [u8], Data)>;
+
+pub fn sorted_run(store: LookupTable) -> Run {
+    let run = store
+        .into_iter()
+        .map(|(name, data, _)| (name, data))
+        .collect::<Vec<_>>();
+    if run.len() > sort::RADIX_SORT_THRESHOLD {
+        sort::radix_sort_by_key(run, |p| p.0)
+    } else {
+        let mut run = run;
+        run.sort_unstable_by_key(|p| p.0);
+        run
+    }
+}
+
+/// Merges the sorted `runs` into a single sorted list, combining the
+/// aggregates of a station found in several runs. The results don't come from
+/// a table, so their slots are all 0.
+pub fn merge_runs(runs: Vec<Run>) -> Vec<(&'static [u8], Data, u32)> {
+    let mut merged: Vec<(&'static [u8], Data, u32)> =
+        Vec::with_capacity(runs.iter().map(Vec::len).max().unwrap_or_default());
+    let mut runs = runs
+        .into_iter()
+        .map(|run| run.into_iter().peekable())
+        .collect::<Vec<_>>();
+
+    // The name at the head of each run, smallest on top
+    let mut heads = BinaryHeap::with_capacity(runs.len());
+    for (idx, run) in runs.iter_mut().enumerate() {
+        if let Some(&(name, _)) = run.peek() {
+            heads.push(Reverse((name, idx)));
+        }
+    }
+
+    while let Some(Reverse((name, idx))) = heads.pop() {
+        let (_, data) = runs[idx].next().unwrap();
+        match merged.last_mut() {
+            Some((last, merged_data, _)) if *last == name => merged_data.merge(&data),
+            _ => merged.push((name, data, 0)),
+        }
+        if let Some(&(name, _)) = runs[idx].peek() {
+            heads.push(Reverse((name, idx)));
+        }
+    }
+
+    merged
+}
@@ -0,0 +1,2011 @@
+//! Aggregates `station;value` measurements into each station's min, mean and
+//! max, as in the One Billion Row Challenge.
+//!
+//! [`aggregate`] is the entry point for embedding, [`run`] is the `brc-rust`
+//! command line.
+
+use std::{
+    borrow::Cow,
+    collections::{hash_map::Entry, HashMap, HashSet},
+    error::Error,
+    fs,
+    io::{self, BufWriter, Write},
+    marker::PhantomData,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+pub mod affinity;
+mod analyze;
+#[cfg(feature = "tar")]
+mod archive;
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod bench;
+mod binary;
+mod check;
+mod flat;
+mod gzip;
+pub mod hasher;
+mod hashstats;
+mod histogram;
+mod index;
+mod json;
+mod kway;
+mod live;
+mod logging;
+mod mmap;
+mod options;
+mod partial;
+mod pivot;
+mod prometheus;
+mod repl;
+mod sink;
+mod slots;
+mod sort;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+pub mod throughput;
+mod util;
+
+use hasher::{hash_name, Djb2, StationHasher};
+use histogram::Histogram;
+pub use live::LiveTable;
+#[cfg(feature = "memmap2")]
+pub use mmap::map_file_memmap2;
+pub use mmap::{map_file, map_file_native, unmap};
+use options::{Encoding, Format, InputFormat, Options, SortOrder};
+use sink::{ReferenceSink, TextSink};
+pub use sink::{ResultSink, StationResult};
+use slots::Slots;
+use throughput::{SystemClock, ThroughputGuard};
+
+/// Slots of a station table before it grows
+pub const MAP_SIZE: usize = 7599;
+
+/// Station that records filtered out by `--filter` are remapped to under
+/// `--other-bucket`
+const OTHER_BUCKET: &[u8] = b"__other__";
+
+/// Files smaller than this are processed on the calling thread by default
+const SMALL_FILE_THRESHOLD: u64 = 1 << 20;
+
+/// Smallest chunk worth handing to a thread of its own
+const MIN_CHUNK_SIZE: u64 = 1 << 20;
+
+/// Bytes a worker parses between updates of the shared progress counter and
+/// checks of whether `--min-throughput` gave up on the run
+const PROGRESS_STEP: usize = 1 << 20;
+
+/// Time given to `--min-throughput` before it starts enforcing the minimum
+const THROUGHPUT_WARMUP: Duration = Duration::from_secs(1);
+const THROUGHPUT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Aggregate of a station's values in tenths of a degree. The extremes are
+/// packed into `i16`s (up to ±3276.7), which leaves room for an `i64` sum, as
+/// billions of rows overflow an `i32`, in 16 bytes. Laid out in a fixed order
+/// with the sum and count every value adds to first.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+struct Data {
+    sum: i64,
+    count: u32,
+    min: i16,
+    max: i16,
+}
+
+/// The aggregate of no values, which the first value merged in overwrites
+/// the extremes of.
+impl Default for Data {
+    fn default() -> Self {
+        Data {
+            min: i16::MAX,
+            max: i16::MIN,
+            sum: 0,
+            count: 0,
+        }
+    }
+}
+
+impl Data {
+    /// Mean in degrees
+    fn mean(&self) -> f64 {
+        self.sum as f64 / self.count as f64 / 10.
+    }
+
+    /// The aggregate in degrees
+    fn stats(&self) -> StationStats {
+        StationStats {
+            min: self.min as f64 / 10.,
+            max: self.max as f64 / 10.,
+            mean: self.mean(),
+            count: self.count as u64,
+        }
+    }
+
+    /// Checks what the aggregate of one or more values always satisfies.
+    fn check_invariants(&self) -> Result<(), String> {
+        if self.count == 0 {
+            return Err("no values".to_string());
+        }
+        if self.min > self.max {
+            return Err(format!("min {} above max {}", self.min, self.max));
+        }
+        let count = self.count as i64;
+        if !(self.min as i64 * count..=self.max as i64 * count).contains(&self.sum) {
+            return Err(format!("sum {} outside of {count} values' range", self.sum));
+        }
+
+        Ok(())
+    }
+
+    /// Folds the aggregate of other values of the same station into this one.
+    fn merge(&mut self, other: &Data) {
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.sum += other.sum;
+        self.count += other.count;
+    }
+}
+
+const _: () = assert!(std::mem::size_of::<Data>() <= 16);
+
+/// A station's name and aggregate, two to a cache line
+type Slot = Option<(&'static [u8], Data)>;
+
+const _: () = assert!(std::mem::size_of::<Slot>() == 32);
+
+/// Open addressing table of the stations. It starts out with `MAP_SIZE` slots
+/// and grows once more than `MAX_LOAD_PERCENT` of them are taken, so probing
+/// always ends at an empty slot. Stations are placed by their hash under `H`.
+struct LookupTable<H: StationHasher = Djb2> {
+    slots: Slots,
+    len: usize,
+    hasher: PhantomData<H>,
+}
+
+/// Occupancy beyond which a `LookupTable` grows
+const MAX_LOAD_PERCENT: usize = 70;
+
+impl<H: StationHasher> LookupTable<H> {
+    fn new() -> Self {
+        Self::with_slots(MAP_SIZE, align_of::<Slot>())
+    }
+
+    /// A worker's table, aligned to cache lines under `--align-tables`.
+    fn for_worker(opts: &Options) -> Self {
+        let align = match opts.align_tables {
+            true => slots::CACHE_LINE,
+            false => align_of::<Slot>(),
+        };
+        Self::with_slots(MAP_SIZE, align)
+    }
+
+    /// A table holding `stations` without growing.
+    fn new_with_capacity(stations: usize) -> Self {
+        Self::with_slots(slots_for(stations), align_of::<Slot>())
+    }
+
+    fn with_slots(size: usize, align: usize) -> Self {
+        // Built on the heap directly, the array alone would take a sizeable
+        // part of a small `--thread-stack-size`
+        LookupTable {
+            slots: Slots::new(size.max(1), align),
+            len: 0,
+            hasher: PhantomData,
+        }
+    }
+
+    /// Number of slots
+    fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Index of the slot holding `k`, or of the empty slot it belongs in,
+    /// probing linearly from its home slot `hash % capacity`.
+    fn find_slot(&self, k: &'static [u8], hash: u32) -> usize {
+        let mut slot_idx = (hash as usize) % self.slots.len();
+
+        loop {
+            match unsafe { self.slots.get_unchecked(slot_idx) } {
+                Some((key, _)) if *key != k => {}
+                _ => return slot_idx,
+            }
+
+            slot_idx = (slot_idx + 1) % self.slots.len();
+        }
+    }
+
+    /// Like `find_slot`, but first grows the table if `k` is new and would
+    /// take it past `MAX_LOAD_PERCENT`.
+    fn find_slot_for_insert(&mut self, k: &'static [u8], hash: u32) -> usize {
+        let slot_idx = self.find_slot(k, hash);
+        if self.slots[slot_idx].is_some() {
+            return slot_idx;
+        }
+
+        self.len += 1;
+        if self.len * 100 <= self.slots.len() * MAX_LOAD_PERCENT {
+            return slot_idx;
+        }
+
+        let len = self.len;
+        let align = self.slots.align();
+        let mut grown = Self::with_slots(slots_for(len * 2), align);
+        logging::debug!(
+            "growing lookup table from {} to {} slots",
+            self.slots.len(),
+            grown.slots.len()
+        );
+        for (k, v, _) in std::mem::replace(self, Self::with_slots(0, align)) {
+            let slot_idx = grown.find_slot(k, H::hash(k));
+            grown.slots[slot_idx] = Some((k, v));
+        }
+        grown.len = len;
+        *self = grown;
+
+        self.find_slot(k, hash)
+    }
+
+    /// Returns the aggregate of `k`, first filling its empty slot with an
+    /// empty aggregate if it isn't in the table yet. A single probe walk
+    /// either way, unless the table has to grow.
+    fn entry(&mut self, k: &'static [u8], hash: u32) -> &mut Data {
+        let slot_idx = self.find_slot_for_insert(k, hash);
+
+        let slot = unsafe { self.slots.get_unchecked_mut(slot_idx) };
+        &mut slot.get_or_insert((k, Data::default())).1
+    }
+
+    /// Merges the aggregates of `other` into this table, whether it's another
+    /// worker's, another file's or another run's.
+    fn merge_into(&mut self, other: LookupTable<H>) {
+        // A probed station's slot differs from its home slot, so the hash is
+        // recomputed rather than taken from the slot index
+        for (k, v, _) in other {
+            self.entry(k, H::hash(k)).merge(&v);
+        }
+    }
+}
+
+/// Number of slots holding `stations` without growing, a prime.
+fn slots_for(stations: usize) -> usize {
+    next_prime((stations * 100).div_ceil(MAX_LOAD_PERCENT))
+}
+
+/// Smallest prime no less than `n`
+fn next_prime(n: usize) -> usize {
+    (n..).find(|&n| is_prime(n)).unwrap()
+}
+
+/// Trial division, the table sizes it's asked about are small
+pub(crate) fn is_prime(n: usize) -> bool {
+    n >= 2
+        && (2..)
+            .take_while(|d| d * d <= n)
+            .all(|d| !n.is_multiple_of(d))
+}
+
+impl<H: StationHasher> IntoIterator for LookupTable<H> {
+    type Item = (&'static [u8], Data, u32);
+
+    type IntoIter = MapIter<H>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        MapIter { idx: 0, map: self }
+    }
+}
+
+struct MapIter<H: StationHasher> {
+    idx: usize,
+    map: LookupTable<H>,
+}
+
+impl<H: StationHasher> Iterator for MapIter<H> {
+    type Item = (&'static [u8], Data, u32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for idx in self.idx..self.map.slots.len() {
+            if let Some((k, v)) = unsafe { self.map.slots.get_unchecked_mut(idx).take() } {
+                self.idx = idx + 1;
+                return Some((k, v, idx as u32));
+            }
+        }
+
+        None
+    }
+}
+
+#[derive(Clone, Copy)]
+/// Leading `#` lines of the input. A `#scale=N` line declares the values to
+/// be integers in units of 1/N.
+struct Header {
+    len: usize,
+    scale: Option<u32>,
+}
+
+fn read_header(data: &[u8]) -> Header {
+    let mut len = 0;
+    let mut scale = None;
+
+    while data.get(len) == Some(&b'#') {
+        let end = data[len..]
+            .iter()
+            .position(|&ch| ch == b'\n')
+            .map_or(data.len(), |pos| len + pos + 1);
+
+        if let Some(value) = data[len..end].strip_prefix(b"#scale=") {
+            scale = std::str::from_utf8(value)
+                .ok()
+                .and_then(|value| value.trim().parse().ok())
+                .filter(|&scale| scale > 0);
+        }
+
+        len = end;
+    }
+
+    Header { len, scale }
+}
+
+#[derive(Clone, Copy)]
+struct ParseResult {
+    place: &'static [u8],
+    place_hash: u32,
+    val: i32,
+    /// Whether the line had a delimiter followed by a digit, false for a
+    /// last line cut short before its value
+    has_value: bool,
+    /// Only filled in by `parse_line_loose` under `--timestamp-col`
+    ts: &'static [u8],
+    next: usize,
+}
+
+/// Maps the file at `path`, with the path in the error. A path of `-` reads
+/// stdin.
+fn load_file(path: &str) -> io::Result<&'static [u8]> {
+    map_input(path).map_err(|err| io::Error::new(err.kind(), format!("{path}: {err}")))
+}
+
+/// Maps the file at `path`, or reads stdin for a path of `-`. Gzipped data is
+/// decompressed into memory, which like the mappings is never freed.
+fn map_input(path: &str) -> io::Result<&'static [u8]> {
+    if path == "-" {
+        return read_stdin();
+    }
+
+    let data = map_file(&fs::File::open(path)?)?;
+    if !gzip::is_gzip(data) {
+        return Ok(data);
+    }
+    let decompressed = gzip::decompress(data);
+    // SAFETY: nothing borrows from the compressed data
+    unsafe { mmap::unmap(data) };
+
+    Ok(decompressed?.leak())
+}
+
+/// Number of worker threads worth spawning for a file of `file_size` bytes:
+/// a single one for small files, otherwise one per `MIN_CHUNK_SIZE` capped at
+/// the available parallelism.
+pub fn recommended_threads(file_size: u64) -> usize {
+    if file_size < SMALL_FILE_THRESHOLD {
+        return 1;
+    }
+
+    available_cpus().min(file_size.div_ceil(MIN_CHUNK_SIZE) as usize)
+}
+
+/// Number of CPUs the workers can run on.
+fn available_cpus() -> usize {
+    available_cpus_from(&thread::available_parallelism)
+}
+
+/// Number of CPUs `query` reports. Restricted sandboxes and unusual cgroup
+/// setups can hide it, a single worker runs then.
+///
+/// ```
+/// use std::{io, num::NonZeroUsize};
+///
+/// assert_eq!(brc_rust::available_cpus_from(&|| Ok(NonZeroUsize::new(8).unwrap())), 8);
+/// assert_eq!(brc_rust::available_cpus_from(&|| Err(io::ErrorKind::Unsupported.into())), 1);
+/// ```
+pub fn available_cpus_from(query: &dyn Fn() -> io::Result<NonZeroUsize>) -> usize {
+    query().map(NonZeroUsize::get).unwrap_or_else(|err| {
+        eprintln!("failed to query the available parallelism, using 1 thread: {err}");
+        1
+    })
+}
+
+/// Pins the calling worker to CPU `idx`. The workers run correctly unpinned,
+/// so a failure is only reported under `--verbose`.
+fn pin_worker(idx: usize, opts: &Options) {
+    if !util::set_cpu_affinity(idx) && opts.verbose {
+        eprintln!("failed to pin worker {idx} to its CPU, running unpinned");
+    }
+}
+
+/// Number of workers asked for with `--threads` or `--leave-cores`, if any.
+fn requested_threads(opts: &Options) -> Option<usize> {
+    if opts.single_thread {
+        return Some(1);
+    }
+    opts.threads.or_else(|| {
+        opts.leave_cores
+            .map(|n| available_cpus().saturating_sub(n).max(1))
+    })
+}
+
+/// Reads the inherited file descriptor `fd` to its end, for `--fd`. Pipes
+/// can't be mapped, so the data is copied into memory that, like the
+/// mappings, is never freed.
+#[cfg(unix)]
+fn read_fd(fd: i32) -> io::Result<&'static [u8]> {
+    use std::{io::Read, os::fd::BorrowedFd};
+
+    // A duplicate fails cleanly if `fd` isn't open, where closing it after
+    // taking ownership would abort
+    let fd = unsafe { BorrowedFd::borrow_raw(fd) }.try_clone_to_owned()?;
+    let mut data = Vec::new();
+    fs::File::from(fd).read_to_end(&mut data)?;
+
+    Ok(data.leak())
+}
+
+#[cfg(not(unix))]
+fn read_fd(_fd: i32) -> io::Result<&'static [u8]> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+/// Maps the file at `path`, or loads its member named by `--tar-member` when
+/// built with the `tar` feature. A path of `-` reads stdin, gzipped inputs are
+/// decompressed.
+fn load_input(path: &str, opts: &Options) -> io::Result<&'static [u8]> {
+    #[cfg(feature = "tar")]
+    if let Some(member) = &opts.tar_member {
+        return archive::load_member(path, member);
+    }
+    #[cfg(not(feature = "tar"))]
+    let _ = opts;
+
+    map_input(path)
+}
+
+/// Reads stdin to its end. Like the descriptors of `--fd` it may be a pipe,
+/// so the data is copied into memory that is never freed either.
+fn read_stdin() -> io::Result<&'static [u8]> {
+    use std::io::Read;
+
+    let mut data = Vec::new();
+    io::stdin().lock().read_to_end(&mut data)?;
+    if gzip::is_gzip(&data) {
+        data = gzip::decompress(&data)?;
+    }
+
+    Ok(data.leak())
+}
+
+/// Part of one of several inputs, handed out to the shared workers
+struct Chunk {
+    data: &'static [u8],
+    header: Header,
+    start: usize,
+    size: usize,
+}
+
+/// Aggregates several inputs with a single set of workers. The inputs are cut
+/// into chunks of about `MIN_CHUNK_SIZE` that the workers take from a shared
+/// queue, so small and large files are balanced together.
+fn cluster_process_all(
+    inputs: &[&'static [u8]],
+    store: &mut LookupTable,
+    opts: &Options,
+    live: Option<&LiveTable>,
+) -> Stats {
+    // Each input whole in a single pass, the reference the splitting
+    // between workers has to agree with
+    if opts.single_thread {
+        let mut stats = Stats {
+            workers: 1,
+            ..Stats::default()
+        };
+        for data in inputs {
+            let header = read_header(data);
+            let monitors = Monitors {
+                progress: None,
+                live,
+            };
+            let size = data.len() - header.len;
+            stats.merge(consume(
+                data, header.len, size, store, opts, &header, monitors,
+            ));
+        }
+        return stats;
+    }
+
+    if let [data] = inputs {
+        return cluster_process(data, store, opts, live);
+    }
+
+    let chunks = split_chunks(inputs);
+    let workers = chunk_workers(inputs, chunks.len(), opts);
+    logging::debug!(
+        "processing {} files in {} chunks with {workers} workers",
+        inputs.len(),
+        chunks.len()
+    );
+
+    if workers <= 1 {
+        let mut stats = Stats {
+            workers: 1,
+            ..Stats::default()
+        };
+        for chunk in &chunks {
+            let monitors = Monitors {
+                progress: None,
+                live,
+            };
+            stats.merge(consume_chunk(chunk, store, opts, monitors));
+        }
+        return stats;
+    }
+
+    let (stores, stats) = process_chunks(&chunks, workers, opts, live, |store| store);
+    merge_stores(store, stores, opts);
+
+    stats
+}
+
+/// Aggregates the inputs like [`cluster_process_all`], but each worker sorts
+/// its own results and they're left to [`kway::merge_runs`], for
+/// `--kway-merge`.
+fn kway_process(
+    inputs: &[&'static [u8]],
+    opts: &Options,
+    live: Option<&LiveTable>,
+) -> (Vec<kway::Run>, Stats) {
+    let chunks = split_chunks(inputs);
+    let workers = chunk_workers(inputs, chunks.len(), opts);
+    logging::debug!(
+        "processing {} files in {} chunks with {workers} sorting workers",
+        inputs.len(),
+        chunks.len()
+    );
+
+    process_chunks(&chunks, workers, opts, live, kway::sorted_run)
+}
+
+/// Cuts the inputs into chunks of about `MIN_CHUNK_SIZE`.
+fn split_chunks(inputs: &[&'static [u8]]) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    for &data in inputs {
+        let header = read_header(data);
+        let body = &data[header.len..];
+        let count = (body.len() as u64).div_ceil(MIN_CHUNK_SIZE).max(1);
+        chunks.extend(
+            chunk_bounds(body, count as usize)
+                .into_iter()
+                .map(|(start, end)| Chunk {
+                    data,
+                    header,
+                    start: header.len + start,
+                    size: end - start,
+                }),
+        );
+    }
+
+    chunks
+}
+
+fn chunk_workers(inputs: &[&'static [u8]], chunks: usize, opts: &Options) -> usize {
+    let size = inputs.iter().map(|data| data.len() as u64).sum::<u64>();
+    let threads = if size < opts.small_file_threshold {
+        1
+    } else {
+        requested_threads(opts).unwrap_or_else(|| recommended_threads(size))
+    };
+
+    threads.min(chunks)
+}
+
+fn consume_chunk(
+    chunk: &Chunk,
+    store: &mut LookupTable,
+    opts: &Options,
+    monitors: Monitors,
+) -> Stats {
+    consume(
+        chunk.data,
+        chunk.start,
+        chunk.size,
+        store,
+        opts,
+        &chunk.header,
+        monitors,
+    )
+}
+
+/// Runs `workers` threads taking chunks from a shared queue, each into a
+/// table of its own that `finish` turns into the worker's result.
+fn process_chunks<T: Send>(
+    chunks: &[Chunk],
+    workers: usize,
+    opts: &Options,
+    live: Option<&LiveTable>,
+    finish: impl Fn(LookupTable) -> T + Sync,
+) -> (Vec<T>, Stats) {
+    let progress = &Progress::default();
+    let finished = &AtomicUsize::new(0);
+    let next = &AtomicUsize::new(0);
+    let finish = &finish;
+
+    let run_worker = move |idx: usize| {
+        if workers > 1 {
+            pin_worker(idx, opts);
+        }
+
+        let monitors = Monitors {
+            progress: opts.min_throughput.map(|_| progress),
+            live,
+        };
+        let mut store = LookupTable::for_worker(opts);
+        let mut stats = Stats::default();
+        while let Some(chunk) = chunks.get(next.fetch_add(1, Ordering::Relaxed)) {
+            stats.merge(consume_chunk(chunk, &mut store, opts, monitors));
+        }
+        finished.fetch_add(1, Ordering::Relaxed);
+
+        (finish(store), stats)
+    };
+
+    let mut results = Vec::with_capacity(workers);
+    let mut stats = Stats {
+        workers,
+        ..Stats::default()
+    };
+    thread::scope(|s| {
+        let monitor = opts.min_throughput.map(|min_gbps| {
+            s.spawn(move || monitor_throughput(min_gbps, progress, finished, workers))
+        });
+
+        let handles = (0..workers)
+            .map(|idx| {
+                worker_builder(opts)
+                    .spawn_scoped(s, move || run_worker(idx))
+                    .expect("failed to spawn worker")
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            let (result, local_stats) = handle.join().unwrap();
+            results.push(result);
+            stats.merge(local_stats);
+        }
+        stats.aborted = monitor.and_then(|monitor| monitor.join().unwrap().err());
+    });
+
+    (results, stats)
+}
+
+fn cluster_process(
+    data: &'static [u8],
+    store: &mut LookupTable,
+    opts: &Options,
+    live: Option<&LiveTable>,
+) -> Stats {
+    let size = data.len() as u64;
+
+    let header = read_header(data);
+    let body_size = size - header.len as u64;
+
+    // Spawning and pinning threads costs more than it saves for small files
+    if size < opts.small_file_threshold {
+        let stats = consume(
+            data,
+            header.len,
+            body_size as _,
+            store,
+            opts,
+            &header,
+            Monitors {
+                progress: None,
+                live,
+            },
+        );
+        return Stats {
+            workers: 1,
+            ..stats
+        };
+    }
+
+    let cpus = worker_count(data, &header, opts);
+    logging::debug!("processing {size} bytes with {cpus} workers");
+    let bounds = &chunk_bounds(&data[header.len..], cpus as usize)
+        .into_iter()
+        .map(|(start, end)| (header.len + start, header.len + end))
+        .collect::<Vec<_>>();
+
+    let progress = &Progress::default();
+    let finished = &AtomicUsize::new(0);
+
+    let run_worker = |idx: usize, store: &mut LookupTable| {
+        pin_worker(idx, opts);
+
+        let (start, end) = bounds[idx];
+        let monitors = Monitors {
+            progress: opts.min_throughput.map(|_| progress),
+            live,
+        };
+        let stats = consume(data, start, end - start, store, opts, &header, monitors);
+        finished.fetch_add(1, Ordering::Relaxed);
+
+        stats
+    };
+
+    // Workers hand over their tables as they finish, so merging overlaps
+    // with the workers still running
+    if opts.streaming_merge {
+        let (tx, rx) = mpsc::channel();
+
+        return thread::scope(|s| {
+            let monitor = opts.min_throughput.map(|min_gbps| {
+                s.spawn(move || monitor_throughput(min_gbps, progress, finished, cpus as _))
+            });
+
+            for idx in 0..cpus as usize {
+                let tx = tx.clone();
+                worker_builder(opts)
+                    .spawn_scoped(s, move || {
+                        let mut local_store = LookupTable::for_worker(opts);
+                        let stats = run_worker(idx, &mut local_store);
+                        tx.send((idx, local_store, stats)).unwrap();
+                    })
+                    .expect("failed to spawn worker");
+            }
+            drop(tx);
+
+            let mut stats = Stats {
+                workers: cpus as usize,
+                ..Stats::default()
+            };
+            affinity::pinned(&affinity::System, opts.pin_merge, || {
+                for (idx, local_store, local_stats) in rx {
+                    if opts.dump_partials {
+                        dump_partial(idx, bounds[idx], &local_store);
+                    }
+                    store.merge_into(local_store);
+                    stats.merge(local_stats);
+                }
+            });
+            stats.aborted = monitor.and_then(|monitor| monitor.join().unwrap().err());
+
+            stats
+        });
+    }
+
+    let mut stores: Vec<LookupTable> = Vec::with_capacity(cpus as usize);
+    for _ in 0..cpus {
+        stores.push(LookupTable::for_worker(opts));
+    }
+
+    let mut stats = Stats {
+        workers: cpus as usize,
+        ..Stats::default()
+    };
+    thread::scope(|s| {
+        let monitor = opts.min_throughput.map(|min_gbps| {
+            s.spawn(move || monitor_throughput(min_gbps, progress, finished, cpus as _))
+        });
+
+        let mut workers = Vec::with_capacity(cpus as usize);
+        for (idx, store) in stores.iter_mut().enumerate() {
+            let worker = worker_builder(opts).spawn_scoped(s, move || run_worker(idx, store));
+            workers.push(worker.expect("failed to spawn worker"));
+        }
+
+        for worker in workers {
+            stats.merge(worker.join().unwrap());
+        }
+        stats.aborted = monitor.and_then(|monitor| monitor.join().unwrap().err());
+    });
+
+    if opts.dump_partials {
+        for (idx, local_store) in stores.iter().enumerate() {
+            dump_partial(idx, bounds[idx], local_store);
+        }
+    }
+    merge_stores(store, stores, opts);
+
+    stats
+}
+
+fn worker_builder(opts: &Options) -> thread::Builder {
+    let builder = thread::Builder::new();
+    match opts.thread_stack_size {
+        Some(size) => builder.stack_size(size),
+        None => builder,
+    }
+}
+
+/// Number of workers `cluster_process` splits a file larger than the
+/// `--small-file-threshold` between.
+fn worker_count(data: &[u8], header: &Header, opts: &Options) -> u64 {
+    // Workers past the number of lines would only get empty chunks
+    let threads = requested_threads(opts).unwrap_or_else(|| recommended_threads(data.len() as u64));
+    let lines = data[header.len..]
+        .split(|&ch| ch == b'\n')
+        .filter(|line| !line.is_empty())
+        .take(threads)
+        .count();
+
+    threads.min(lines).max(1) as u64
+}
+
+/// Offset of the first line starting at or after `offset`, if any. A line
+/// starts at 0 or right after a newline, so a line straddling `offset` is
+/// skipped.
+fn line_start(data: &[u8], mut offset: usize) -> Option<usize> {
+    if offset == 0 {
+        return Some(0);
+    }
+
+    while offset <= data.len() {
+        if data[offset - 1] == b'\n' {
+            return Some(offset);
+        }
+        offset += 1;
+    }
+
+    None
+}
+
+/// Prints how `cluster_process` would split `data` for `--explain`: the byte
+/// range of the lines each worker parses and the CPU it's pinned to.
+fn explain(data: &[u8], opts: &Options, mut w: impl Write) -> io::Result<()> {
+    let header = read_header(data);
+    let size = data.len() as u64;
+    if size < opts.small_file_threshold {
+        writeln!(w, "1 worker, below the small file threshold")?;
+        return writeln!(w, "worker 0: bytes {}..{size}, not pinned", header.len);
+    }
+
+    let workers = worker_count(data, &header, opts);
+    writeln!(w, "{workers} workers")?;
+    let bounds = chunk_bounds(&data[header.len..], workers as usize);
+    for (idx, (start, end)) in bounds.into_iter().enumerate() {
+        let (start, end) = (header.len + start, header.len + end);
+        writeln!(w, "worker {idx}: bytes {start}..{end}, cpu {idx}")?;
+    }
+
+    Ok(())
+}
+
+/// Splits `data` between `threads` workers into ranges of whole lines: each
+/// `[start, end)` begins at a line start, and together they're contiguous and
+/// cover all of `data`. Every boundary is the first line start at or after
+/// the even split of `chunk_range`, so a line is in the range its first byte
+/// falls in. A range falling within a line is left empty, one range per
+/// worker all the same.
+pub fn chunk_bounds(data: &[u8], threads: usize) -> Vec<(usize, usize)> {
+    let threads = threads.max(1) as u64;
+    let snap = |offset: u64| line_start(data, offset as usize).unwrap_or(data.len());
+    (0..threads)
+        .map(|idx| {
+            let (start, end) = chunk_range(data.len() as u64, threads, idx);
+            (snap(start), snap(end))
+        })
+        .collect()
+}
+
+/// Byte range of the body handed to worker `idx` out of `workers`, before
+/// it's snapped to line boundaries. The bytes left over by the integer
+/// division go one each to the first workers, so the ranges differ by at most
+/// one byte.
+fn chunk_range(body_size: u64, workers: u64, idx: u64) -> (u64, u64) {
+    let (size, remains) = (body_size / workers, body_size % workers);
+    let start = idx * size + idx.min(remains);
+    let end = start + size + (idx < remains) as u64;
+
+    (start, end)
+}
+
+/// Fails, and stops the workers at their next progress update, when they
+/// fall below `min_gbps` of throughput.
+fn monitor_throughput(
+    min_gbps: f64,
+    progress: &Progress,
+    finished: &AtomicUsize,
+    workers: usize,
+) -> Result<(), String> {
+    let guard = ThroughputGuard::new(SystemClock::start(), min_gbps, THROUGHPUT_WARMUP);
+
+    while finished.load(Ordering::Relaxed) < workers {
+        thread::sleep(THROUGHPUT_POLL_INTERVAL);
+
+        if let Err(err) = guard.check(progress.bytes.load(Ordering::Relaxed)) {
+            progress.aborted.store(true, Ordering::Relaxed);
+            return Err(err);
+        }
+    }
+
+    Ok(())
+}
+
+/// Reports a worker's share of the data, the byte range of the file it
+/// parsed and what it found there. Large disparities between workers point
+/// at skewed chunking.
+fn dump_partial(idx: usize, (start, end): (usize, usize), local_store: &LookupTable) {
+    let (stations, records) = local_store
+        .slots
+        .iter()
+        .flatten()
+        .fold((0, 0u64), |(stations, records), (_, data)| {
+            (stations + 1, records + data.count as u64)
+        });
+
+    eprintln!("worker {idx}: bytes {start}..{end}, {stations} stations, {records} records");
+}
+
+/// Merges the workers' tables into `store`. They're first merged pairwise on
+/// threads of their own, round after round, so the merge isn't a serial tail
+/// growing with the number of workers. Under `--pin-merge` they're all merged
+/// on the pinned thread instead.
+fn merge_stores(store: &mut LookupTable, mut stores: Vec<LookupTable>, opts: &Options) {
+    while opts.pin_merge.is_none() && stores.len() > 2 {
+        stores = thread::scope(|s| {
+            let mut rest = stores.into_iter();
+            let mut handles = Vec::new();
+            let mut odd = None;
+            while let Some(mut left) = rest.next() {
+                match rest.next() {
+                    Some(right) => handles.push(s.spawn(move || {
+                        left.merge_into(right);
+                        left
+                    })),
+                    None => odd = Some(left),
+                }
+            }
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .chain(odd)
+                .collect()
+        });
+    }
+
+    affinity::pinned(&affinity::System, opts.pin_merge, || {
+        for local_store in stores {
+            store.merge_into(local_store);
+        }
+    });
+}
+
+/// Aggregates the lines in the `size` bytes at `start`, a range of whole
+/// lines as `chunk_bounds` splits them.
+fn consume(
+    data: &'static [u8],
+    start: usize,
+    size: usize,
+    store: &mut LookupTable,
+    opts: &Options,
+    header: &Header,
+    monitors: Monitors,
+) -> Stats {
+    let chunk_end = start + size;
+    let mut state = WorkerState {
+        live: monitors.live,
+        ..Default::default()
+    };
+    let mut readptr = start;
+    let mut reported = start;
+    while readptr < chunk_end {
+        if let Some(end) = process(data, readptr, store, opts, header, &mut state) {
+            readptr = end + 1;
+        } else {
+            break;
+        }
+
+        if let Some(progress) = monitors.progress {
+            if readptr - reported >= PROGRESS_STEP {
+                progress
+                    .bytes
+                    .fetch_add((readptr - reported) as u64, Ordering::Relaxed);
+                reported = readptr;
+                if progress.aborted.load(Ordering::Relaxed) {
+                    break;
+                }
+            }
+        }
+    }
+
+    state.stats
+}
+
+/// Shared state a worker reports to while it runs
+#[derive(Clone, Copy)]
+struct Monitors<'a> {
+    /// Bytes parsed so far, for `--min-throughput`
+    progress: Option<&'a Progress>,
+    /// Running aggregates, for `--watch`
+    live: Option<&'a LiveTable>,
+}
+
+/// How far the workers got, for `--min-throughput`
+#[derive(Default)]
+struct Progress {
+    bytes: AtomicU64,
+    /// Set once the throughput fell below the minimum, the workers stop then
+    aborted: AtomicBool,
+}
+
+/// What parsing found besides the aggregates kept in the `LookupTable`
+#[derive(Default)]
+struct Stats {
+    malformed: usize,
+    /// Offset and content of the earliest malformed line, for `--strict`
+    first_malformed: Option<(usize, &'static [u8])>,
+    /// Timestamps of each station's extremes, for `--timestamp-col`
+    extremes: HashMap<&'static [u8], Extremes>,
+    /// Start offsets of each station's lines, for `--index`
+    offsets: HashMap<&'static [u8], Vec<usize>>,
+    /// Wrapping sum of each station's mixed values, for `--station-checksums`.
+    /// Addition makes it independent of the order lines are seen in.
+    checksums: HashMap<&'static [u8], u64>,
+    /// Counts of each station's values, for `--percentiles`
+    histograms: HashMap<&'static [u8], Histogram>,
+    /// Worker threads the inputs were split between, for `--verbose`
+    workers: usize,
+    /// Why `--min-throughput` stopped the workers before the end
+    aborted: Option<String>,
+}
+
+impl Stats {
+    /// Counts the line at `offset` as malformed.
+    fn malformed(&mut self, data: &'static [u8], offset: usize) {
+        self.malformed += 1;
+        if self.first_malformed.is_none_or(|(first, _)| offset < first) {
+            let end = skip_line(data, offset).unwrap_or(offset);
+            self.first_malformed = Some((offset, &data[offset..end]));
+        }
+    }
+
+    fn merge(&mut self, other: Stats) {
+        self.malformed += other.malformed;
+        if let Some((offset, line)) = other.first_malformed {
+            if self.first_malformed.is_none_or(|(first, _)| offset < first) {
+                self.first_malformed = Some((offset, line));
+            }
+        }
+        self.workers = self.workers.max(other.workers);
+        self.aborted = self.aborted.take().or(other.aborted);
+
+        for (station, offsets) in other.offsets {
+            self.offsets.entry(station).or_default().extend(offsets);
+        }
+
+        for (station, checksum) in other.checksums {
+            let total = self.checksums.entry(station).or_default();
+            *total = total.wrapping_add(checksum);
+        }
+
+        for (station, other) in other.histograms {
+            match self.histograms.entry(station) {
+                Entry::Occupied(mut entry) => entry.get_mut().merge(&other),
+                Entry::Vacant(entry) => {
+                    entry.insert(other);
+                }
+            }
+        }
+
+        for (station, other) in other.extremes {
+            match self.extremes.entry(station) {
+                Entry::Occupied(mut entry) => entry.get_mut().merge(&other),
+                Entry::Vacant(entry) => {
+                    entry.insert(other);
+                }
+            }
+        }
+    }
+}
+
+/// A station's extreme values along with the timestamps they were measured
+/// at. Ties keep the timestamp seen first.
+struct Extremes {
+    min: i32,
+    min_ts: &'static [u8],
+    max: i32,
+    max_ts: &'static [u8],
+}
+
+impl Extremes {
+    fn new(val: i32, ts: &'static [u8]) -> Self {
+        Extremes {
+            min: val,
+            min_ts: ts,
+            max: val,
+            max_ts: ts,
+        }
+    }
+
+    fn merge(&mut self, other: &Extremes) {
+        if other.min < self.min {
+            self.min = other.min;
+            self.min_ts = other.min_ts;
+        }
+        if other.max > self.max {
+            self.max = other.max;
+            self.max_ts = other.max_ts;
+        }
+    }
+}
+
+#[derive(Default)]
+struct WorkerState<'a> {
+    stats: Stats,
+    /// Lines seen so far in this worker's chunk, for `--stride`
+    lines: u64,
+    /// Start offset and parse of the previously recorded line, for `--rle`
+    last_line: Option<(usize, ParseResult)>,
+    /// Table mirroring every recorded line, for `--watch`
+    live: Option<&'a LiveTable>,
+}
+
+fn process(
+    data: &'static [u8],
+    offset: usize,
+    store: &mut LookupTable,
+    opts: &Options,
+    header: &Header,
+    state: &mut WorkerState,
+) -> Option<usize> {
+    // A blank line would otherwise run into the name on the next one
+    match data.get(offset..)? {
+        [b'\n', ..] => return Some(offset),
+        [b'\r', b'\n', ..] => return Some(offset + 1),
+        _ => {}
+    }
+
+    // Striding counts lines from the start of each worker's chunk rather than
+    // the file, so with several workers the kept lines only approximate every
+    // Nth line of the file
+    if opts.stride > 1 {
+        let line = state.lines;
+        state.lines += 1;
+
+        if !line.is_multiple_of(opts.stride as u64) {
+            return skip_line(data, offset);
+        }
+    }
+
+    // A line identical to the previous one folds the same value into the same
+    // slot, so skip parsing and hashing it again
+    if opts.rle {
+        if let Some((last_start, last)) = state.last_line {
+            if let Some(line) = data.get(last_start..=last.next) {
+                if data.get(offset..offset + line.len()) == Some(line) {
+                    record(store, &last);
+                    track(state, opts, &last, offset);
+                    return Some(offset + line.len() - 1);
+                }
+            }
+        }
+    }
+
+    let parsed = if opts.thousands_sep.is_some()
+        || header.scale.is_some()
+        || opts.timestamp_col
+        || opts.pivot
+    {
+        parse_line_loose(data, offset, opts, header)
+    } else {
+        parse_line::<Djb2>(data, offset, opts.delim)
+    };
+
+    if let Some(mut parsed) = parsed {
+        // Nothing to record, whether or not lines are validated: a last line
+        // without a delimiter would be a station with an empty name
+        if !parsed.has_value {
+            state.stats.malformed(data, offset);
+            return skip_line(data, offset);
+        }
+
+        // Before anything else, a line without a delimiter would take the
+        // next line's name along
+        if opts.validate && !is_well_formed(data, offset, &parsed, opts, header) {
+            state.stats.malformed(data, offset);
+            return skip_line(data, offset);
+        }
+
+        if opts.strict_utf8 && std::str::from_utf8(parsed.place).is_err() {
+            state.stats.malformed(data, offset);
+            return Some(parsed.next);
+        }
+
+        // Beyond the challenge's -99.9..99.9 but also beyond what `Data` keeps
+        if i16::try_from(parsed.val).is_err() {
+            state.stats.malformed(data, offset);
+            return Some(parsed.next);
+        }
+
+        if let Some(filter) = &opts.filter {
+            if !filter.contains(parsed.place) {
+                if !opts.other_bucket {
+                    return Some(parsed.next);
+                }
+
+                parsed.place = OTHER_BUCKET;
+                parsed.place_hash = hash_name(OTHER_BUCKET);
+            }
+        }
+
+        record(store, &parsed);
+        track(state, opts, &parsed, offset);
+        if opts.rle {
+            state.last_line = Some((offset, parsed));
+        }
+
+        Some(parsed.next)
+    } else {
+        None
+    }
+}
+
+/// Reports a recorded line to everything besides the `LookupTable` that
+/// follows the records.
+fn track(state: &mut WorkerState, opts: &Options, parsed: &ParseResult, offset: usize) {
+    if let Some(live) = state.live {
+        live.record(parsed.place, parsed.place_hash, parsed.val);
+    }
+    if opts.index.is_some() {
+        state
+            .stats
+            .offsets
+            .entry(parsed.place)
+            .or_default()
+            .push(offset);
+    }
+    if opts.station_checksums {
+        let checksum = state.stats.checksums.entry(parsed.place).or_default();
+        *checksum = checksum.wrapping_add(mix_value(parsed.val));
+    }
+    if !opts.percentiles.is_empty() {
+        state
+            .stats
+            .histograms
+            .entry(parsed.place)
+            .or_insert_with(Histogram::new)
+            .record(parsed.val);
+    }
+    if opts.timestamp_col {
+        let extremes = Extremes::new(parsed.val, parsed.ts);
+        match state.stats.extremes.entry(parsed.place) {
+            Entry::Occupied(mut entry) => entry.get_mut().merge(&extremes),
+            Entry::Vacant(entry) => {
+                entry.insert(extremes);
+            }
+        }
+    }
+}
+
+/// Spreads a value over all 64 bits (the splitmix64 finalizer), so sums of
+/// different multisets of values rarely agree.
+fn mix_value(val: i32) -> u64 {
+    let mut x = (val as i64 as u64).wrapping_add(0x9e3779b97f4a7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+/// Returns the offset of the newline ending the line at `offset`.
+fn skip_line(data: &'static [u8], offset: usize) -> Option<usize> {
+    if offset >= data.len() {
+        return None;
+    }
+
+    let end = data[offset..]
+        .iter()
+        .position(|&ch| ch == b'\n')
+        .map_or(data.len(), |pos| offset + pos);
+
+    Some(end)
+}
+
+fn record(store: &mut LookupTable, parsed: &ParseResult) {
+    let val = parsed.val as i16;
+    let data = store.entry(parsed.place, parsed.place_hash);
+    data.min = data.min.min(val);
+    data.max = data.max.max(val);
+    data.sum += parsed.val as i64;
+    data.count += 1;
+}
+
+fn parse_line<H: StationHasher>(
+    data: &'static [u8],
+    offset: usize,
+    delim_ch: u8,
+) -> Option<ParseResult> {
+    if offset >= data.len() {
+        return None;
+    }
+
+    // Find the delimiter and compute hash till that point
+    let (mut idx, loc_hash) = H::scan(data, offset, delim_ch);
+
+    let loc: &[u8] = if idx < data.len() {
+        unsafe { data.get_unchecked(offset..idx) }
+    } else {
+        &[]
+    };
+
+    // Skip past delimiter
+    idx += 1;
+
+    let isneg = data.get(idx) == Some(&b'-');
+    if isneg {
+        idx += 1;
+    }
+
+    // Any number of integer digits (none for `.c`), then a single fractional
+    // digit, anything finer is truncated. Saturating keeps runaway digits out
+    // of the `i16` range rather than wrapping back into it.
+    let mut val: i32 = 0;
+    let int_start = idx;
+    while let Some(&ch @ b'0'..=b'9') = data.get(idx) {
+        val = val.saturating_mul(10).saturating_add((ch - b'0') as i32);
+        idx += 1;
+    }
+    val = val.saturating_mul(10);
+    let mut has_value = idx > int_start;
+
+    if data.get(idx) == Some(&b'.') {
+        idx += 1;
+        if let Some(&ch @ b'0'..=b'9') = data.get(idx) {
+            val = val.saturating_add((ch - b'0') as i32);
+            idx += 1;
+            has_value = true;
+        }
+        while data.get(idx).is_some_and(u8::is_ascii_digit) {
+            idx += 1;
+        }
+    }
+
+    if isneg {
+        val = -val;
+    }
+
+    Some(ParseResult {
+        place: loc,
+        place_hash: loc_hash,
+        val,
+        has_value,
+        ts: &[],
+        next: skip_blanks(data, idx),
+    })
+}
+
+/// Whether the line at `offset` has a delimiter and ends in a `[-]d{1,3}.d`
+/// value, for `--validate`. Values under `--thousands-sep` or a `#scale`
+/// header take other forms and are left to the parser.
+fn is_well_formed(
+    data: &'static [u8],
+    offset: usize,
+    parsed: &ParseResult,
+    opts: &Options,
+    header: &Header,
+) -> bool {
+    let end = skip_line(data, offset).unwrap_or(offset);
+    // The parser stops at the first byte past the value it doesn't expect
+    if parsed.next != end {
+        return false;
+    }
+    let line = &data[offset..end];
+    let Some(delim) = line.iter().rposition(|&ch| ch == opts.delim) else {
+        return false;
+    };
+    if opts.thousands_sep.is_some() || header.scale.is_some() {
+        return true;
+    }
+
+    let mut value = &line[delim + 1..];
+    while let [rest @ .., b' ' | b'\t' | b'\r'] = value {
+        value = rest;
+    }
+    let digits = value.strip_prefix(b"-").unwrap_or(value);
+    match digits {
+        [int @ .., b'.', frac] => {
+            (1..=3).contains(&int.len())
+                && int.iter().all(u8::is_ascii_digit)
+                && frac.is_ascii_digit()
+        }
+        _ => false,
+    }
+}
+
+/// Skips the spaces and tabs trailing a value and the `\r` of a CRLF line
+/// ending, which each line may or may not have, returning the offset of the
+/// newline after them.
+#[inline(always)]
+fn skip_blanks(data: &[u8], mut idx: usize) -> usize {
+    while idx < data.len() && matches!(data[idx], b' ' | b'\t' | b'\r') {
+        idx += 1;
+    }
+
+    idx
+}
+
+/// Slower counterpart of `parse_line` that skips `--thousands-sep` inside the
+/// value, accepts any number of integer digits, divides the value by the
+/// header's scale and reads the `--timestamp-col` in front of the value.
+fn parse_line_loose(
+    data: &'static [u8],
+    offset: usize,
+    opts: &Options,
+    header: &Header,
+) -> Option<ParseResult> {
+    if offset >= data.len() {
+        return None;
+    }
+
+    let end = data[offset..]
+        .iter()
+        .position(|&ch| ch == b'\n')
+        .map_or(data.len(), |pos| offset + pos);
+    let line = &data[offset..end];
+    // Under `--pivot` the place is the composite `key;subkey`
+    let fields = if opts.pivot { 2 } else { 1 };
+    let delim = line
+        .iter()
+        .enumerate()
+        .filter(|&(_, &ch)| ch == opts.delim)
+        .nth(fields - 1)
+        .map_or(line.len(), |(idx, _)| idx);
+    let place = &line[..delim];
+
+    let mut value = line.get(delim + 1..).unwrap_or_default();
+    let mut ts: &[u8] = &[];
+    if opts.timestamp_col {
+        let delim = value
+            .iter()
+            .position(|&ch| ch == opts.delim)
+            .unwrap_or(value.len());
+        ts = &value[..delim];
+        value = value.get(delim + 1..).unwrap_or_default();
+    }
+
+    let thousands_sep = opts.thousands_sep;
+    let val = parse_value(
+        value
+            .iter()
+            .copied()
+            .filter(|&ch| Some(ch) != thousands_sep),
+    );
+    let val = val / header.scale.unwrap_or(1) as i64;
+
+    Some(ParseResult {
+        place,
+        place_hash: hash_name(place),
+        // Saturated values are still out of range of the `i16` check in
+        // `process`
+        val: val.clamp(i32::MIN as i64, i32::MAX as i64) as i32,
+        has_value: delim < line.len() && value.iter().any(u8::is_ascii_digit),
+        ts,
+        next: end,
+    })
+}
+
+/// Parses a decimal value into tenths, anything beyond the first fractional
+/// digit is truncated.
+fn parse_value(value: impl Iterator<Item = u8>) -> i64 {
+    let mut val: i64 = 0;
+    let mut isneg = false;
+    let mut frac_digits = None;
+
+    for ch in value {
+        match (ch, frac_digits) {
+            (b'-', _) => isneg = true,
+            (b'.', None) => frac_digits = Some(0),
+            (b'0'..=b'9', None | Some(0)) => {
+                val = val * 10 + (ch - b'0') as i64;
+                frac_digits = frac_digits.map(|n| n + 1);
+            }
+            _ => {}
+        }
+    }
+
+    if frac_digits != Some(1) {
+        val *= 10;
+    }
+
+    if isneg {
+        -val
+    } else {
+        val
+    }
+}
+
+/// Formats tenths the way Java's `Double.toString` prints one decimal value.
+fn fmt_tenths(tenths: i64) -> String {
+    let sign = if tenths < 0 { "-" } else { "" };
+    format!("{sign}{}.{}", tenths.abs() / 10, tenths.abs() % 10)
+}
+
+/// The name as text. Invalid UTF-8 is replaced with U+FFFD unless
+/// `--strict-utf8` already dropped the lines of such names.
+fn decode_name<'a>(name: &'a [u8], encoding: &Encoding) -> Cow<'a, str> {
+    match encoding {
+        Encoding::Utf8 => String::from_utf8_lossy(name),
+        // Latin-1 bytes are the first 256 Unicode code points
+        Encoding::Latin1 => Cow::Owned(name.iter().map(|&ch| ch as char).collect()),
+    }
+}
+
+/// Writes the name to the text output, UTF-8 names as the bytes they are
+/// whether valid or not.
+fn write_name(mut w: impl Write, name: &[u8], encoding: &Encoding) -> io::Result<()> {
+    match encoding {
+        Encoding::Utf8 => w.write_all(name),
+        Encoding::Latin1 => w.write_all(decode_name(name, encoding).as_bytes()),
+    }
+}
+
+fn conv_num(num: i32) -> f32 {
+    num as f32 / 10.
+}
+
+/// Aggregate of a station's values, in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StationStats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub count: u64,
+}
+
+/// Aggregates the `station;value` lines of `data` on `threads` workers, or on
+/// as many as its size warrants when `threads` is 0. Returns each station
+/// with its aggregate, sorted by name.
+///
+/// ```
+/// let data = b"Hamburg;12.0\nBulawayo;8.9\nHamburg;34.2\n";
+/// let stations = brc_rust::aggregate(data, 1);
+///
+/// assert_eq!(stations.len(), 2);
+/// let (name, hamburg) = &stations[1];
+/// assert_eq!(name, b"Hamburg");
+/// assert_eq!((hamburg.min, hamburg.mean, hamburg.max), (12.0, 23.1, 34.2));
+/// assert_eq!(hamburg.count, 2);
+/// ```
+pub fn aggregate(data: &[u8], threads: usize) -> Vec<(Vec<u8>, StationStats)> {
+    aggregate_sorted(data, &library_options(threads))
+        .into_iter()
+        .map(|(name, data, _)| (name.to_vec(), data.stats()))
+        .collect()
+}
+
+/// Aggregates `data` like [`aggregate`] and writes the stations to `w` in the
+/// text format of the command line, e.g. `{Bulawayo=8.9/8.9/8.9}`.
+///
+/// ```
+/// let data = b"Hamburg;12.0\nBulawayo;8.9\nHamburg;34.2\n";
+/// let mut out = Vec::new();
+/// brc_rust::write_text(data, 1, &mut out).unwrap();
+///
+/// assert_eq!(out, b"{Bulawayo=8.9/8.9/8.9, Hamburg=12.0/23.1/34.2}");
+/// ```
+pub fn write_text(data: &[u8], threads: usize, w: impl Write) -> io::Result<()> {
+    let opts = library_options(threads);
+    let stations = aggregate_sorted(data, &opts);
+    sink::write_all(
+        &mut TextSink::new(w, &opts, &Stats::default(), MAP_SIZE),
+        &stations,
+    )
+}
+
+/// Aggregates `data` like [`aggregate`] and streams the stations sorted by
+/// name through `sink`.
+pub fn aggregate_into(data: &[u8], threads: usize, sink: &mut dyn ResultSink) -> io::Result<()> {
+    let stations = aggregate_sorted(data, &library_options(threads));
+    sink::write_all(sink, &stations)
+}
+
+/// Defaults of the command line but for the number of workers, as many as
+/// the size of the input warrants when `threads` is 0. A requested count is
+/// honoured for small inputs too.
+fn library_options(threads: usize) -> Options {
+    match threads {
+        0 => Options::default(),
+        _ => Options {
+            threads: Some(threads),
+            small_file_threshold: 0,
+            ..Options::default()
+        },
+    }
+}
+
+/// The stations of `data` sorted by name, with the slots they were aggregated
+/// in.
+fn aggregate_sorted<'a>(data: &'a [u8], opts: &Options) -> Vec<(&'a [u8], Data, u32)> {
+    // SAFETY: the table and the stats referencing `data` are dropped before
+    // returning, the names returned borrow it no longer than the caller does
+    let data: &'static [u8] = unsafe { &*(data as *const [u8]) };
+
+    let mut store = LookupTable::new();
+    cluster_process(data, &mut store, opts, None);
+
+    let mut stations = store.into_iter().collect::<Vec<_>>();
+    stations.sort_unstable_by_key(|p| p.0);
+
+    stations
+}
+
+/// Prints the lowest and highest value of all stations to stderr, each with
+/// the stations that measured it in name order, for `--global-extremes`.
+fn print_global_extremes(sorted_store: &[(&[u8], Data, u32)], encoding: &Encoding) {
+    let holders = |val: i16, extreme: fn(&Data) -> i16| {
+        sorted_store
+            .iter()
+            .filter(|(_, data, _)| extreme(data) == val)
+            .map(|(name, _, _)| decode_name(name, encoding))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    // Seeded stations without values keep the extremes of no values
+    let measured = sorted_store.iter().filter(|(_, data, _)| data.count > 0);
+    let min = measured.clone().map(|(_, data, _)| data.min).min();
+    let max = measured.map(|(_, data, _)| data.max).max();
+    if let (Some(min), Some(max)) = (min, max) {
+        let min_holders = holders(min, |data| data.min);
+        let max_holders = holders(max, |data| data.max);
+        eprintln!("global min: {} ({min_holders})", fmt_tenths(min as i64));
+        eprintln!("global max: {} ({max_holders})", fmt_tenths(max as i64));
+    }
+}
+
+/// Maps the file at `path` and [`aggregate`]s it.
+pub fn aggregate_file(path: &str) -> io::Result<Vec<(Vec<u8>, StationStats)>> {
+    let data = load_file(path)?;
+    let stations = aggregate(data, 0);
+    // SAFETY: the workers have joined and the names were copied out
+    unsafe { mmap::unmap(data) };
+
+    Ok(stations)
+}
+
+/// Loads the input files and descriptors, merging those that aren't
+/// measurements (`--merge-partials` and binary inputs) into `store` right
+/// away. With `--continue-on-error` the inputs that fail are collected in
+/// `failed` instead.
+fn load_inputs(
+    opts: &Options,
+    store: &mut LookupTable,
+    failed: &mut Vec<String>,
+) -> Result<Vec<&'static [u8]>, Box<dyn Error>> {
+    let mut inputs = Vec::with_capacity(opts.paths.len() + opts.fds.len());
+    for path in &opts.paths {
+        let result = if opts.merge_partials {
+            partial::read(path, store).map(|_| None)
+        } else if opts.input_format == InputFormat::Binary {
+            load_input(path, opts)
+                .and_then(|data| binary::aggregate(data, store, opts))
+                .map(|_| None)
+        } else {
+            load_input(path, opts).map(Some)
+        };
+        match result {
+            Ok(input) => inputs.extend(input),
+            Err(err) if opts.continue_on_error => {
+                eprintln!("skipping {path}: {err}");
+                failed.push(path.clone());
+            }
+            Err(err) => return Err(format!("{path}: {err}").into()),
+        }
+    }
+    for &fd in &opts.fds {
+        match read_fd(fd) {
+            Ok(input) => inputs.push(input),
+            Err(err) if opts.continue_on_error => {
+                eprintln!("skipping fd {fd}: {err}");
+                failed.push(format!("fd {fd}"));
+            }
+            Err(err) => return Err(format!("fd {fd}: {err}").into()),
+        }
+    }
+
+    Ok(inputs)
+}
+
+/// The buffered writer the results go to: stdout or the `--output` file,
+/// compressed if its name ends in `.gz` with the `gzip` feature.
+fn output(opts: &Options) -> Result<Box<dyn Write>, Box<dyn Error>> {
+    let capacity = opts.output_buffer_size;
+    let Some(path) = &opts.output else {
+        return Ok(Box::new(BufWriter::with_capacity(
+            capacity,
+            io::stdout().lock(),
+        )));
+    };
+
+    let file = fs::File::create(path).map_err(|err| format!("{path}: {err}"))?;
+    #[cfg(feature = "gzip")]
+    if path.ends_with(".gz") {
+        return Ok(Box::new(BufWriter::with_capacity(
+            capacity,
+            gzip::encoder(file),
+        )));
+    }
+
+    Ok(Box::new(BufWriter::with_capacity(capacity, file)))
+}
+
+/// Runs the command line with the process' arguments.
+pub fn run() -> Result<(), Box<dyn Error>> {
+    run_with_args(std::env::args().skip(1))
+}
+
+/// Runs the command line with `args`, the arguments after the program name.
+/// Diagnostics go to the `log` logger installed by the caller, if any.
+pub fn run_with_args(args: impl IntoIterator<Item = String>) -> Result<(), Box<dyn Error>> {
+    let started = Instant::now();
+    let opts = Options::parse(args.into_iter())?;
+
+    let mut out = output(&opts)?;
+
+    if let Some(station) = &opts.lookup {
+        match flat::lookup(load_file(&opts.paths[0])?, station.as_bytes()) {
+            Some(data) => sink::write_all(
+                &mut TextSink::new(&mut out, &opts, &Stats::default(), MAP_SIZE),
+                &[(station.as_bytes(), data, 0)],
+            )?,
+            None => eprintln!("{station} not found"),
+        }
+        return Ok(());
+    }
+
+    if opts.analyze {
+        for path in &opts.paths {
+            if opts.paths.len() > 1 {
+                writeln!(out, "{path}:")?;
+            }
+            let lengths = analyze::LineLengths::collect(load_file(path)?);
+            write!(out, "{lengths}")?;
+        }
+        out.flush()?;
+        return Ok(());
+    }
+
+    if opts.explain {
+        explain(load_file(&opts.paths[0])?, &opts, &mut out)?;
+        out.flush()?;
+        return Ok(());
+    }
+
+    if opts.tune {
+        let inputs = opts
+            .paths
+            .iter()
+            .map(|path| load_file(path))
+            .collect::<io::Result<Vec<_>>>()?;
+        let results = hashstats::tune(inputs, opts.delim);
+        for (size, stats) in &results {
+            writeln!(out, "size={size} {stats}")?;
+        }
+        if let Some((size, _)) = results.first() {
+            writeln!(out, "recommended MAP_SIZE: {size}")?;
+        }
+        out.flush()?;
+        return Ok(());
+    }
+
+    let mut store = LookupTable::new();
+
+    let mut stats = Stats::default();
+    let mut runs = Vec::new();
+    let mut failed = Vec::new();
+    let live = opts.watch.as_ref().map(|_| LiveTable::new());
+    let done = AtomicBool::new(false);
+    thread::scope(|s| {
+        if let (Some(station), Some(live)) = (&opts.watch, &live) {
+            s.spawn(|| live::watch(station, live, opts.rounding, &done));
+        }
+
+        let result = load_inputs(&opts, &mut store, &mut failed).map(|inputs| {
+            if opts.kway_merge {
+                (runs, stats) = kway_process(&inputs, &opts, live.as_ref());
+            } else {
+                stats = cluster_process_all(&inputs, &mut store, &opts, live.as_ref());
+            }
+        });
+        // Also stops the watcher when loading failed
+        done.store(true, Ordering::Release);
+        result
+    })?;
+    if let Some(err) = stats.aborted.take() {
+        return Err(err.into());
+    }
+    if !failed.is_empty() {
+        eprintln!(
+            "failed to process {} file(s): {}",
+            failed.len(),
+            failed.join(", ")
+        );
+    }
+    if stats.malformed > 0 {
+        eprintln!("skipped {} malformed lines", stats.malformed);
+    }
+    if let (true, Some((offset, line))) = (opts.strict, stats.first_malformed) {
+        return Err(format!(
+            "malformed line at byte {offset}: {}",
+            String::from_utf8_lossy(line)
+        )
+        .into());
+    }
+
+    if let Some(path) = &opts.seed_stations {
+        let names = load_file(path)?
+            .split(|&ch| ch == b'\n')
+            .map(|name| name.strip_suffix(b"\r").unwrap_or(name))
+            .filter(|name| !name.is_empty());
+        for name in names {
+            store.entry(name, hash_name(name));
+        }
+    }
+
+    if opts.hash_stats {
+        eprintln!("{}", hashstats::of_table(&store));
+    }
+
+    // Under `--kway-merge`, `store` only holds what was merged while loading
+    // and the seeded stations, one more run to merge
+    let table_size = store.capacity();
+    let mut v = if opts.kway_merge {
+        runs.push(kway::sorted_run(store));
+        kway::merge_runs(runs)
+    } else {
+        store.into_iter().collect::<Vec<_>>()
+    };
+    let lines = v.iter().map(|(_, data, _)| data.count as u64).sum::<u64>();
+    let stations = v.iter().filter(|(_, data, _)| data.count > 0).count();
+    // Once per station, rather than per line in the parser
+    if opts.strict && opts.encoding == Encoding::Utf8 {
+        for (name, _, _) in &v {
+            if let Err(err) = std::str::from_utf8(name) {
+                return Err(format!(
+                    "invalid UTF-8 in station name at byte {}: {}",
+                    err.valid_up_to(),
+                    String::from_utf8_lossy(name)
+                )
+                .into());
+            }
+        }
+    }
+    // A station in several slots would be split into separate aggregates
+    if opts.verify_unique {
+        let mut names = HashSet::with_capacity(v.len());
+        if let Some((name, _, slot)) = v.iter().find(|(name, _, _)| !names.insert(*name)) {
+            return Err(format!(
+                "{} occupies more than one slot, the second at {slot}",
+                decode_name(name, &opts.encoding)
+            )
+            .into());
+        }
+    }
+    if opts.verify_invariants {
+        // Seeded stations without values are the only ones without any
+        let unseeded = |data: &Data| opts.seed_stations.is_none() || data.count > 0;
+        for (name, data, _) in v.iter().filter(|(_, data, _)| unseeded(data)) {
+            if let Err(err) = data.check_invariants() {
+                return Err(format!("{}: {err}", decode_name(name, &opts.encoding)).into());
+            }
+        }
+    }
+    // Stand in for the lines that were skipped by `--stride`
+    if opts.stride > 1 {
+        for (name, data, _) in v.iter_mut() {
+            let count = data.count.checked_mul(opts.stride);
+            let sum = data.sum.checked_mul(opts.stride as i64);
+            let (Some(count), Some(sum)) = (count, sum) else {
+                return Err(format!(
+                    "{}: count scaled by --stride {} overflows",
+                    decode_name(name, &opts.encoding),
+                    opts.stride
+                )
+                .into());
+            };
+            (data.count, data.sum) = (count, sum);
+        }
+    }
+
+    v.retain(|(_, data, _)| data.count >= opts.min_count);
+    match opts.sort {
+        // Already sorted
+        SortOrder::Bytes if opts.kway_merge => {}
+        SortOrder::Bytes if v.len() > sort::RADIX_SORT_THRESHOLD => {
+            v = sort::radix_sort_by_key(v, |p| p.0);
+        }
+        SortOrder::Bytes => v.sort_unstable_by_key(|p| p.0),
+        // Names folding alike fall back to byte order, names are unique so
+        // the order is total either way
+        SortOrder::Unicode => v.sort_by_cached_key(|p| {
+            let name = decode_name(p.0, &opts.encoding);
+            (sort::collation_key(&name), p.0)
+        }),
+    }
+
+    if opts.global_extremes {
+        print_global_extremes(&v, &opts.encoding);
+    }
+
+    let pivoted;
+    let v = if opts.pivot {
+        pivoted = pivot::rows(&v, opts.delim);
+        pivoted
+            .iter()
+            .map(|(name, data)| (name.as_slice(), *data, 0))
+            .collect()
+    } else {
+        v
+    };
+
+    if opts.repl {
+        repl::run(
+            io::stdin().lock(),
+            &mut out,
+            &v,
+            &opts.encoding,
+            opts.rounding,
+        )?;
+    } else {
+        // Under `--check` the output is kept in memory to be compared
+        let mut checked = Vec::new();
+        let w: &mut dyn Write = match opts.check {
+            Some(_) => &mut checked,
+            None => &mut out,
+        };
+        let mut sink: Box<dyn ResultSink> = match opts.format {
+            Format::Text if opts.reference_compat => {
+                Box::new(ReferenceSink::new(w, &opts.encoding))
+            }
+            Format::Text => Box::new(TextSink::new(w, &opts, &stats, table_size)),
+            Format::Flat => Box::new(flat::FlatSink::new(w)),
+            Format::Partial => Box::new(partial::PartialSink::new(w)),
+            Format::Prometheus => Box::new(prometheus::PrometheusSink::new(w, &opts)),
+            #[cfg(feature = "sqlite")]
+            Format::Sqlite => Box::new(sqlite::SqliteSink::new(w, &opts.encoding)?),
+            #[cfg(not(feature = "sqlite"))]
+            Format::Sqlite => unreachable!("rejected by Options::parse"),
+            Format::Json => Box::new(json::JsonSink::new(w, &opts)),
+        };
+        sink::write_all(sink.as_mut(), &v)?;
+        drop(sink);
+
+        if let Some(path) = &opts.check {
+            out.write_all(&checked)?;
+            out.flush()?;
+            let expected = fs::read(path).map_err(|err| format!("{path}: {err}"))?;
+            check::compare(&expected, &checked).map_err(|err| format!("{path}: {err}"))?;
+        }
+    }
+
+    if let Some(path) = &opts.index {
+        fs::File::create(path)
+            .and_then(|file| index::write(BufWriter::new(file), stats.offsets))
+            .map_err(|err| format!("{path}: {err}"))?;
+    }
+
+    if opts.verbose {
+        eprintln!(
+            "lines: {lines}, malformed: {}, stations: {stations}, threads: {}, elapsed: {:.3}s",
+            stats.malformed,
+            stats.workers,
+            started.elapsed().as_secs_f64()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+//! Aggregates shared between the workers and readers that want to see them
+//! while processing is still running, e.g. for `--watch`.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{fmt_tenths, hash_name, options::Rounding, sink::fmt_mean, Data, StationStats};
+
+/// Stations are spread over this many independently locked shards so workers
+/// recording different stations rarely wait on each other
+const SHARDS: usize = 64;
+
+const WATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+type Shard = Mutex<HashMap<&'static [u8], Data>>;
+
+/// Aggregates recorded by any number of threads, readable at any time while
+/// they're still being recorded.
+pub struct LiveTable {
+    shards: Box<[Shard]>,
+}
+
+impl Default for LiveTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LiveTable {
+    pub fn new() -> Self {
+        LiveTable {
+            shards: (0..SHARDS).map(|_| Mutex::default()).collect(),
+        }
+    }
+
+    /// Adds `val`, in tenths of a degree, to the aggregate of `name`, whose
+    /// [`Djb2`](crate::hasher::Djb2) hash is `hash`.
+    pub fn record(&self, name: &'static [u8], hash: u32, val: i32) {
+        let mut shard = self.shards[hash as usize % SHARDS].lock().unwrap();
+        let data = shard.entry(name).or_insert(Data {
+            min: val as i16,
+            max: val as i16,
+            sum: 0,
+            count: 0,
+        });
+        data.min = data.min.min(val as i16);
+        data.max = data.max.max(val as i16);
+        data.sum += val as i64;
+        data.count += 1;
+    }
+
+    /// The station's aggregate of the values recorded so far, `None` before
+    /// its first value. Values recorded concurrently are either wholly in it
+    /// or not at all.
+    pub fn snapshot(&self, name: &[u8]) -> Option<StationStats> {
+        self.data(name).map(|data| data.stats())
+    }
+
+    fn data(&self, name: &[u8]) -> Option<Data> {
+        let shard = self.shards[hash_name(name) as usize % SHARDS]
+            .lock()
+            .unwrap();
+        shard.get(name).copied()
+    }
+}
+
+/// Prints `station`'s aggregate to stderr whenever it changes until `done` is
+/// set, the final aggregate included, each of them once. The mean is rounded
+/// like the output's.
+pub(crate) fn watch(station: &[u8], live: &LiveTable, rounding: Rounding, done: &AtomicBool) {
+    let mut last_count = 0;
+    loop {
+        let finished = done.load(Ordering::Acquire);
+
+        if let Some(data) = live.data(station) {
+            if data.count != last_count {
+                last_count = data.count;
+                eprintln!(
+                    "{}: {} records, {}/{}/{}",
+                    String::from_utf8_lossy(station),
+                    data.count,
+                    fmt_tenths(data.min as i64),
+                    fmt_mean(&data, rounding),
+                    fmt_tenths(data.max as i64)
+                );
+            }
+        }
+
+        if finished {
+            break;
+        }
+        thread::sleep(WATCH_INTERVAL);
+    }
+}
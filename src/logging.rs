@@ -0,0 +1,27 @@
+//! Internal diagnostics go through the `log` crate when the `logging` feature
+//! is on, so a host application decides where they end up. Without it warnings
+//! fall back to stderr and debug messages are dropped. Messages the user asked
+//! for or has to act on are printed to stderr directly instead, they mustn't
+//! depend on a logger's filter.
+
+#[cfg(feature = "logging")]
+pub(crate) use log::{debug, warn};
+
+#[cfg(not(feature = "logging"))]
+macro_rules! stderr_warn {
+    ($($arg:tt)+) => {
+        eprintln!($($arg)+)
+    };
+}
+
+#[cfg(not(feature = "logging"))]
+macro_rules! discard_debug {
+    ($($arg:tt)+) => {
+        if false {
+            eprintln!($($arg)+)
+        }
+    };
+}
+
+#[cfg(not(feature = "logging"))]
+pub(crate) use {discard_debug as debug, stderr_warn as warn};
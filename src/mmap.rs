@@ -0,0 +1,220 @@
+//! Read-only mappings of the input files. Hand-rolled bindings of `mmap` on
+//! Unix and of `CreateFileMappingW`/`MapViewOfFile` on Windows are the
+//! default, the `memmap2` feature swaps in that crate instead. Both backends
+//! stay reachable by name, [`map_file_native`] and `map_file_memmap2`.
+//!
+//! The slices are `'static` so workers and results can borrow from them
+//! freely. The command line keeps its mappings until the process exits,
+//! callers that map files repeatedly release them with [`unmap`].
+
+use std::{fs, io};
+
+#[cfg(unix)]
+use std::{
+    ffi::{c_int, c_void},
+    os::fd::AsRawFd,
+    slice,
+};
+
+#[cfg(windows)]
+use std::{ffi::c_void, os::windows::io::AsRawHandle, ptr, slice};
+
+#[cfg(unix)]
+extern "C" {
+    pub fn mmap(
+        addr: *mut c_void,
+        len: u64,
+        prot: c_int,
+        flags: c_int,
+        fd: c_int,
+        offset: u64,
+    ) -> *mut c_void;
+    pub fn munmap(addr: *mut c_void, len: usize) -> c_int;
+    pub fn madvise(addr: *mut c_void, len: usize, advice: c_int) -> c_int;
+}
+
+/// Maps the whole file, sized by an fstat of the descriptor being mapped. The
+/// length of the returned slice is the one size to use for the file: another
+/// `metadata()` call could disagree with the mapping if the file is resized in
+/// between. Truncating the file while it's mapped still faults on access.
+pub fn map_file(file: &fs::File) -> io::Result<&'static [u8]> {
+    #[cfg(feature = "memmap2")]
+    return map_file_memmap2(file);
+
+    #[cfg(not(feature = "memmap2"))]
+    map_file_native(file)
+}
+
+/// Unmaps a slice returned by [`map_file`], [`map_file_native`] or
+/// `map_file_memmap2`.
+///
+/// # Safety
+///
+/// Nothing may borrow from `data` anymore, despite its `'static` lifetime.
+pub unsafe fn unmap(data: &'static [u8]) {
+    #[cfg(feature = "memmap2")]
+    if unmap_memmap2(data) {
+        return;
+    }
+
+    unmap_native(data)
+}
+
+/// Maps the whole file through the hand-rolled `mmap` binding, like
+/// [`map_file`] does without the `memmap2` feature.
+#[cfg(unix)]
+pub fn map_file_native(file: &fs::File) -> io::Result<&'static [u8]> {
+    const PROT_READ: i32 = 0x1;
+    const MAP_PRIVATE: i32 = 0x2;
+    const MAP_FAILED: *mut c_void = !0 as *mut c_void;
+
+    let size = file.metadata()?.len();
+    // Zero-length mappings are rejected
+    if size == 0 {
+        return Ok(&[]);
+    }
+
+    let res = unsafe {
+        mmap(
+            core::ptr::null_mut(),
+            size,
+            PROT_READ,
+            MAP_PRIVATE,
+            file.as_raw_fd(),
+            0,
+        )
+    };
+
+    if res == MAP_FAILED {
+        return Err(io::Error::last_os_error());
+    }
+    advise_sequential(res, size as _);
+
+    Ok(unsafe { slice::from_raw_parts(res as *const _ as *const u8, size as _) })
+}
+
+/// Tells the kernel the mapping is read front to back, so it reads ahead
+/// further than it would by default on a cold page cache. Only a hint, a
+/// failure is ignored.
+///
+/// `MADV_WILLNEED` isn't given as well: it reads the whole file in before the
+/// workers start, which was slower on a cold cache than parsing along the
+/// readahead.
+#[cfg(unix)]
+fn advise_sequential(addr: *mut c_void, len: usize) {
+    const MADV_SEQUENTIAL: c_int = 2;
+
+    if unsafe { madvise(addr, len, MADV_SEQUENTIAL) } != 0 {
+        let err = io::Error::last_os_error();
+        crate::logging::debug!("madvise of the mapping failed: {err}");
+    }
+}
+
+#[cfg(unix)]
+unsafe fn unmap_native(data: &'static [u8]) {
+    if !data.is_empty() {
+        munmap(data.as_ptr() as *mut c_void, data.len());
+    }
+}
+
+#[cfg(windows)]
+extern "system" {
+    fn CreateFileMappingW(
+        file: *mut c_void,
+        attributes: *const c_void,
+        protect: u32,
+        max_size_high: u32,
+        max_size_low: u32,
+        name: *const u16,
+    ) -> *mut c_void;
+    fn MapViewOfFile(
+        mapping: *mut c_void,
+        access: u32,
+        offset_high: u32,
+        offset_low: u32,
+        len: usize,
+    ) -> *mut c_void;
+    fn CloseHandle(handle: *mut c_void) -> i32;
+    fn UnmapViewOfFile(base: *const c_void) -> i32;
+}
+
+/// Maps the whole file through `MapViewOfFile`, like [`map_file`] does
+/// without the `memmap2` feature. The view keeps the file mapping alive, so
+/// its handle is closed right away.
+#[cfg(windows)]
+pub fn map_file_native(file: &fs::File) -> io::Result<&'static [u8]> {
+    const PAGE_READONLY: u32 = 0x02;
+    const FILE_MAP_READ: u32 = 0x04;
+
+    let size = file.metadata()?.len();
+    // Zero-length mappings are rejected
+    if size == 0 {
+        return Ok(&[]);
+    }
+
+    let mapping = unsafe {
+        CreateFileMappingW(
+            file.as_raw_handle(),
+            ptr::null(),
+            PAGE_READONLY,
+            0,
+            0,
+            ptr::null(),
+        )
+    };
+    if mapping.is_null() {
+        return Err(io::Error::last_os_error());
+    }
+
+    let view = unsafe { MapViewOfFile(mapping, FILE_MAP_READ, 0, 0, 0) };
+    let err = io::Error::last_os_error();
+    unsafe { CloseHandle(mapping) };
+    if view.is_null() {
+        return Err(err);
+    }
+
+    Ok(unsafe { slice::from_raw_parts(view as *const u8, size as _) })
+}
+
+#[cfg(windows)]
+unsafe fn unmap_native(data: &'static [u8]) {
+    if !data.is_empty() {
+        UnmapViewOfFile(data.as_ptr().cast());
+    }
+}
+
+/// The `memmap2` mappings handed out, kept until they're unmapped.
+#[cfg(feature = "memmap2")]
+static MAPPINGS: std::sync::Mutex<Vec<memmap2::Mmap>> = std::sync::Mutex::new(Vec::new());
+
+/// Maps the whole file with `memmap2`, which sizes the mapping from the mapped
+/// descriptor as well.
+#[cfg(feature = "memmap2")]
+pub fn map_file_memmap2(file: &fs::File) -> io::Result<&'static [u8]> {
+    let mmap = unsafe { memmap2::Mmap::map(file)? };
+    // Only a hint, as without the feature
+    #[cfg(unix)]
+    if let Err(err) = mmap.advise(memmap2::Advice::Sequential) {
+        crate::logging::debug!("madvise of the mapping failed: {err}");
+    }
+    // The mapped memory doesn't move with the `Mmap` owning it
+    let data = unsafe { std::slice::from_raw_parts(mmap.as_ptr(), mmap.len()) };
+    MAPPINGS.lock().unwrap().push(mmap);
+
+    Ok(data)
+}
+
+/// Drops the `memmap2` mapping of `data`, false if it wasn't mapped by
+/// `memmap2`.
+#[cfg(feature = "memmap2")]
+fn unmap_memmap2(data: &'static [u8]) -> bool {
+    let mut mappings = MAPPINGS.lock().unwrap();
+    let idx = mappings
+        .iter()
+        .position(|mmap| mmap.as_ptr() == data.as_ptr());
+    if let Some(idx) = idx {
+        mappings.swap_remove(idx);
+    }
+
+    idx.is_some()
+}
@@ -0,0 +1,502 @@
+use std::collections::HashSet;
+
+const USAGE: &str = "Usage: <bin> [options] <path-to-measurements.txt>...
+
+A path of `-` reads the measurements from stdin, gzipped inputs are decompressed (`gzip` feature).
+
+Options:
+    --hash-stats                    Report hash slot collisions of the stations on stderr
+    --small-file-threshold <bytes>  Process smaller files on a single thread [default: 1048576]
+    --strict-utf8                   Skip lines whose station name is not valid UTF-8
+    --format <format>               Output format: `text` (or `default`), `flat` a binary file for mmap
+                                    consumers, `partial` keeping the sums for --merge-partials,
+                                    `prometheus` metrics, a `sqlite` database (`sqlite` feature) or a `json` array
+                                    [default: text]
+    --lookup <station>              Read <path> as a `flat` result file and print the station's entry
+    --output-buffer-size <bytes>    Capacity of the buffered output writer [default: 1048576]
+    --rle                           Fold lines identical to the previous one without re-parsing
+    --filter <station,...>          Only aggregate the listed stations
+    --other-bucket                  Aggregate stations dropped by --filter into `__other__`
+    --min-count <n>                 Leave out stations with fewer than <n> measurements
+    --streaming-merge               Merge each worker's results as soon as it finishes
+    --thousands-sep <byte>          Ignore this byte inside values, e.g. `,` for `1,234.5`
+    --encoding <utf8|latin1>        Encoding of station names, printed as UTF-8 [default: utf8]
+    --min-throughput <GBps>         Abort when throughput falls below this after a short warmup
+    --stride <n>                    Only aggregate every <n>th line, scaling the counts by <n>
+    --analyze                       Print the distribution of line lengths instead of aggregating
+    --continue-on-error             Skip input files that can't be read instead of aborting
+    --dump-partials                 Print each worker's station and record counts before merging
+    --timestamp-col                 Lines are `station;timestamp;value`, print when extremes occurred
+    --reference-compat              Match the Java reference implementation's output byte for byte
+    --watch <station>               Print the station's running aggregate to stderr while processing
+    --threads <n>                   Number of worker threads, capped at the number of lines
+                                    [default: $BRC_THREADS or by file size and cores]
+    --merge-partials                Read the paths as `partial` outputs and combine them
+    --pin-merge <cpu>               Merge the workers' results on a single thread pinned to <cpu>
+    --index <path>                  Write the byte offsets of each station's lines to <path>
+    --pivot                         Lines are `key;subkey;value`, aggregate per key and per subkey
+    --verify-unique                 Abort if a station occupies more than one slot after merging
+    --tune                          Compare the collisions of the stations across table sizes
+    --tar-member <name>             Read the paths as tar archives and aggregate their member <name>
+    --rounding <mode>               Round means to one decimal: nearest (half up, the default), ceil, floor or trunc
+    --thread-stack-size <bytes>     Stack size of the worker threads [default: the platform's]
+    --show-slots                    Print the table slot and probe distance of each station
+    --station-checksums             Print an order-independent checksum of each station's values
+    --repl                          Read queries about the stations from stdin instead of printing them
+    --leave-cores <n>               Use all but <n> of the available cores, at least one
+    --input-format <text|binary>    Format of the input files: `text` lines or `binary` fixed-width
+                                    records [default: text]
+    --explain                       Print how the file would be split between workers and exit
+    --global-extremes               Print the lowest and highest value overall and their stations to stderr
+    --fd <n>                        Also read measurements from the inherited file descriptor <n>, e.g. a
+                                    pipe; can be given several times
+    --seed-stations <path>          Always print the stations listed one per line in <path>, as NA without values
+    --kway-merge                    Experimental: workers sort their own results, which are merged
+                                    into sorted output instead of sorting one table
+    --delim <byte>                  Separator between the fields of a line, `\\t` for a tab [default: ;]
+    --verify-invariants             Abort if a merged aggregate is inconsistent, e.g. its min above its max
+    --output <path>                 Write the results to <path> instead of stdout, gzipped if it ends in `.gz` (`gzip` feature)
+    --verbose                       Print the lines, stations, threads and time taken, and workers left unpinned, to stderr
+    --strict                        Abort on a station name that is not valid UTF-8 or a malformed line, implies --validate
+    --single-thread                 Aggregate each input whole on the main thread, without splitting or pinning
+    --percentiles <p,...>           Print these percentiles of each station's values, e.g. 50,95,99
+    --check <path>                  Compare the output with the file at <path>, failing on the first station that differs
+    --align-tables                  Align the workers' station tables to cache lines
+    --sort <order>                  Order the stations by bytes, the challenge's order and the default, or by
+                                    unicode, letters folded with their case and accents
+    --validate                      Skip lines whose value isn't `[-]d{1,3}.d` or that lack a delimiter";
+
+#[derive(Debug, Default, PartialEq)]
+pub enum Format {
+    #[default]
+    Text,
+    Flat,
+    Partial,
+    Prometheus,
+    Sqlite,
+    Json,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub enum InputFormat {
+    #[default]
+    Text,
+    Binary,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    Latin1,
+}
+
+/// Order of the stations in the output, for `--sort`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortOrder {
+    /// Byte order of the names, as the challenge's output is sorted
+    Bytes,
+    /// By `sort::collation_key` of the decoded names
+    Unicode,
+}
+
+/// How `--rounding` renders a mean to one decimal
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Rounding {
+    /// Half up, toward positive infinity, as the reference implementation
+    /// rounds
+    #[default]
+    Nearest,
+    Ceil,
+    Floor,
+    Trunc,
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub paths: Vec<String>,
+    pub hash_stats: bool,
+    /// Files smaller than this many bytes are processed on the calling thread
+    pub small_file_threshold: u64,
+    pub strict_utf8: bool,
+    pub format: Format,
+    pub lookup: Option<String>,
+    pub output_buffer_size: usize,
+    pub rle: bool,
+    pub filter: Option<HashSet<Vec<u8>>>,
+    pub other_bucket: bool,
+    pub min_count: u32,
+    pub streaming_merge: bool,
+    pub thousands_sep: Option<u8>,
+    pub encoding: Encoding,
+    pub min_throughput: Option<f64>,
+    pub stride: u32,
+    pub analyze: bool,
+    pub continue_on_error: bool,
+    pub dump_partials: bool,
+    pub timestamp_col: bool,
+    pub reference_compat: bool,
+    pub watch: Option<Vec<u8>>,
+    pub threads: Option<usize>,
+    pub merge_partials: bool,
+    pub pin_merge: Option<usize>,
+    pub index: Option<String>,
+    pub pivot: bool,
+    pub verify_unique: bool,
+    pub tune: bool,
+    pub tar_member: Option<String>,
+    pub rounding: Rounding,
+    pub thread_stack_size: Option<usize>,
+    pub show_slots: bool,
+    pub station_checksums: bool,
+    pub repl: bool,
+    pub leave_cores: Option<usize>,
+    pub input_format: InputFormat,
+    pub explain: bool,
+    pub global_extremes: bool,
+    pub fds: Vec<i32>,
+    pub seed_stations: Option<String>,
+    pub kway_merge: bool,
+    /// Separator between the station and the value
+    pub delim: u8,
+    pub verify_invariants: bool,
+    pub output: Option<String>,
+    pub verbose: bool,
+    pub strict: bool,
+    pub single_thread: bool,
+    pub percentiles: Vec<f64>,
+    pub check: Option<String>,
+    pub align_tables: bool,
+    pub sort: SortOrder,
+    pub validate: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            paths: Vec::new(),
+            hash_stats: false,
+            small_file_threshold: crate::SMALL_FILE_THRESHOLD,
+            strict_utf8: false,
+            format: Format::Text,
+            lookup: None,
+            output_buffer_size: 1 << 20,
+            rle: false,
+            filter: None,
+            other_bucket: false,
+            min_count: 0,
+            streaming_merge: false,
+            thousands_sep: None,
+            encoding: Encoding::Utf8,
+            min_throughput: None,
+            stride: 1,
+            analyze: false,
+            continue_on_error: false,
+            dump_partials: false,
+            timestamp_col: false,
+            reference_compat: false,
+            watch: None,
+            threads: None,
+            merge_partials: false,
+            pin_merge: None,
+            index: None,
+            pivot: false,
+            verify_unique: false,
+            tune: false,
+            tar_member: None,
+            rounding: Rounding::default(),
+            thread_stack_size: None,
+            show_slots: false,
+            station_checksums: false,
+            repl: false,
+            leave_cores: None,
+            input_format: InputFormat::Text,
+            explain: false,
+            global_extremes: false,
+            fds: Vec::new(),
+            seed_stations: None,
+            kway_merge: false,
+            delim: b';',
+            verify_invariants: false,
+            output: None,
+            verbose: false,
+            strict: false,
+            single_thread: false,
+            percentiles: Vec::new(),
+            check: None,
+            align_tables: false,
+            sort: SortOrder::Bytes,
+            validate: false,
+        }
+    }
+}
+
+impl Options {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut opts = Options::default();
+
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("missing value for {flag}\n{USAGE}"))
+            };
+
+            match flag {
+                "--hash-stats" => opts.hash_stats = true,
+                "--strict-utf8" => opts.strict_utf8 = true,
+                "--rle" => opts.rle = true,
+                "--format" => {
+                    opts.format = match value()?.as_str() {
+                        "text" | "default" => Format::Text,
+                        "flat" => Format::Flat,
+                        "partial" => Format::Partial,
+                        "prometheus" => Format::Prometheus,
+                        "sqlite" => Format::Sqlite,
+                        "json" => Format::Json,
+                        other => return Err(format!("unknown format: {other}\n{USAGE}")),
+                    }
+                }
+                "--lookup" => opts.lookup = Some(value()?),
+                "--output-buffer-size" => opts.output_buffer_size = parse_num(flag, &value()?)?,
+                "--small-file-threshold" => opts.small_file_threshold = parse_num(flag, &value()?)?,
+                "--filter" => {
+                    opts.filter = Some(value()?.split(',').map(|s| s.as_bytes().to_vec()).collect())
+                }
+                "--other-bucket" => opts.other_bucket = true,
+                "--min-count" => opts.min_count = parse_num(flag, &value()?)?,
+                "--streaming-merge" => opts.streaming_merge = true,
+                "--thousands-sep" => {
+                    opts.thousands_sep = match value()?.as_bytes() {
+                        &[sep] => Some(sep),
+                        _ => return Err(format!("--thousands-sep must be a single byte\n{USAGE}")),
+                    }
+                }
+                "--encoding" => {
+                    opts.encoding = match value()?.as_str() {
+                        "utf8" => Encoding::Utf8,
+                        "latin1" => Encoding::Latin1,
+                        other => return Err(format!("unknown encoding: {other}\n{USAGE}")),
+                    }
+                }
+                "--min-throughput" => opts.min_throughput = Some(parse_num(flag, &value()?)?),
+                "--stride" => {
+                    opts.stride = match parse_num(flag, &value()?)? {
+                        0 => return Err(format!("{flag} must be at least 1")),
+                        n => n,
+                    };
+                }
+                "--analyze" => opts.analyze = true,
+                "--continue-on-error" => opts.continue_on_error = true,
+                "--dump-partials" => opts.dump_partials = true,
+                "--timestamp-col" => opts.timestamp_col = true,
+                "--reference-compat" => opts.reference_compat = true,
+                "--watch" => opts.watch = Some(value()?.into_bytes()),
+                "--threads" => opts.threads = Some(parse_threads(flag, &value()?)?),
+                "--merge-partials" => opts.merge_partials = true,
+                "--pin-merge" => opts.pin_merge = Some(parse_num(flag, &value()?)?),
+                "--index" => opts.index = Some(value()?),
+                "--pivot" => opts.pivot = true,
+                "--verify-unique" => opts.verify_unique = true,
+                "--tune" => opts.tune = true,
+                "--tar-member" => opts.tar_member = Some(value()?),
+                "--rounding" => {
+                    opts.rounding = match value()?.as_str() {
+                        "nearest" => Rounding::Nearest,
+                        "ceil" => Rounding::Ceil,
+                        "floor" => Rounding::Floor,
+                        "trunc" => Rounding::Trunc,
+                        other => return Err(format!("unknown rounding: {other}\n{USAGE}")),
+                    }
+                }
+                "--thread-stack-size" => opts.thread_stack_size = Some(parse_num(flag, &value()?)?),
+                "--show-slots" => opts.show_slots = true,
+                "--station-checksums" => opts.station_checksums = true,
+                "--repl" => opts.repl = true,
+                "--leave-cores" => opts.leave_cores = Some(parse_num(flag, &value()?)?),
+                "--input-format" => {
+                    opts.input_format = match value()?.as_str() {
+                        "text" => InputFormat::Text,
+                        "binary" => InputFormat::Binary,
+                        other => return Err(format!("unknown input format: {other}\n{USAGE}")),
+                    }
+                }
+                "--explain" => opts.explain = true,
+                "--global-extremes" => opts.global_extremes = true,
+                "--fd" => opts.fds.push(parse_num(flag, &value()?)?),
+                "--seed-stations" => opts.seed_stations = Some(value()?),
+                "--kway-merge" => opts.kway_merge = true,
+                "--delim" => {
+                    opts.delim = match value()?.as_bytes() {
+                        b"\\t" => b'\t',
+                        &[delim] if !matches!(delim, b'\n' | b'\r' | b'0'..=b'9' | b'.' | b'-') => {
+                            delim
+                        }
+                        _ => {
+                            return Err(format!(
+                            "--delim must be a single byte that can't be part of a value\n{USAGE}"
+                        ))
+                        }
+                    }
+                }
+                "--verify-invariants" => opts.verify_invariants = true,
+                "--output" => opts.output = Some(value()?),
+                "--verbose" => opts.verbose = true,
+                "--strict" => {
+                    opts.strict = true;
+                    opts.validate = true;
+                }
+                "--single-thread" => opts.single_thread = true,
+                "--percentiles" => {
+                    opts.percentiles = value()?
+                        .split(',')
+                        .map(|p| match parse_num::<f64>(flag, p)? {
+                            p if p > 0. && p <= 100. => Ok(p),
+                            _ => Err(format!("{flag} must be above 0 and at most 100")),
+                        })
+                        .collect::<Result<_, _>>()?;
+                }
+                "--check" => opts.check = Some(value()?),
+                "--align-tables" => opts.align_tables = true,
+                "--sort" => {
+                    opts.sort = match value()?.as_str() {
+                        "bytes" => SortOrder::Bytes,
+                        "unicode" => SortOrder::Unicode,
+                        other => return Err(format!("unknown sort order: {other}\n{USAGE}")),
+                    }
+                }
+                "--validate" => opts.validate = true,
+                flag if flag.starts_with("--") => {
+                    return Err(format!("unknown option: {flag}\n{USAGE}"))
+                }
+                _ => opts.paths.push(arg),
+            }
+        }
+
+        if opts.thousands_sep == Some(opts.delim) {
+            return Err("--thousands-sep can't be the delimiter".to_string());
+        }
+
+        if opts.other_bucket && opts.filter.is_none() {
+            return Err(format!("--other-bucket requires --filter\n{USAGE}"));
+        }
+
+        if cfg!(not(feature = "sqlite")) && opts.format == Format::Sqlite {
+            return Err("--format sqlite requires building with the `sqlite` feature".to_string());
+        }
+
+        let gzipped = opts
+            .output
+            .as_ref()
+            .is_some_and(|path| path.ends_with(".gz"));
+        if cfg!(not(feature = "gzip")) && gzipped {
+            return Err("a `.gz` --output requires building with the `gzip` feature".to_string());
+        }
+
+        if cfg!(not(feature = "tar")) && opts.tar_member.is_some() {
+            return Err("--tar-member requires building with the `tar` feature".to_string());
+        }
+
+        if opts.threads.is_some() && opts.leave_cores.is_some() {
+            return Err(format!(
+                "--threads can't be combined with --leave-cores\n{USAGE}"
+            ));
+        }
+
+        if opts.single_thread
+            && (opts.threads.is_some()
+                || opts.leave_cores.is_some()
+                || opts.kway_merge
+                || opts.streaming_merge)
+        {
+            return Err(format!(
+                "--single-thread can't be combined with --threads, --leave-cores, --kway-merge or --streaming-merge\n{USAGE}"
+            ));
+        }
+
+        if opts.threads.is_none() && opts.leave_cores.is_none() && !opts.single_thread {
+            if let Ok(threads) = std::env::var("BRC_THREADS") {
+                opts.threads = Some(parse_threads("BRC_THREADS", &threads)?);
+            }
+        }
+
+        if opts.show_slots && opts.pivot {
+            return Err(format!(
+                "--show-slots can't be combined with --pivot\n{USAGE}"
+            ));
+        }
+
+        if opts.kway_merge
+            && (opts.show_slots || opts.streaming_merge || opts.dump_partials || opts.hash_stats)
+        {
+            return Err(format!(
+                "--kway-merge can't be combined with --show-slots, --streaming-merge, --dump-partials or --hash-stats\n{USAGE}"
+            ));
+        }
+
+        if opts.paths.is_empty() && opts.fds.is_empty() {
+            return Err(USAGE.to_string());
+        }
+
+        // Several inputs are cut into chunks the workers share, there's no
+        // worker's range to report nor a table per worker to stream
+        if opts.paths.len() + opts.fds.len() > 1 && (opts.dump_partials || opts.streaming_merge) {
+            return Err(format!(
+                "--dump-partials and --streaming-merge take a single input\n{USAGE}"
+            ));
+        }
+
+        let stdin = opts.paths.iter().any(|path| path == "-");
+        if stdin && (opts.repl || opts.merge_partials) {
+            return Err(format!(
+                "reading stdin with `-` can't be combined with --repl or --merge-partials\n{USAGE}"
+            ));
+        }
+
+        if opts.check.is_some() && opts.repl {
+            return Err(format!("--check can't be combined with --repl\n{USAGE}"));
+        }
+
+        if opts.paths.is_empty() && (opts.lookup.is_some() || opts.explain) {
+            return Err(format!(
+                "--lookup and --explain read an input file\n{USAGE}"
+            ));
+        }
+
+        if !opts.fds.is_empty() && (opts.merge_partials || opts.input_format == InputFormat::Binary)
+        {
+            return Err(format!("--fd only reads text measurements\n{USAGE}"));
+        }
+
+        if opts.index.is_some() && opts.paths.len() > 1 {
+            return Err(format!("--index takes a single input file\n{USAGE}"));
+        }
+
+        if opts.explain && opts.paths.len() > 1 {
+            return Err(format!("--explain takes a single input file\n{USAGE}"));
+        }
+
+        Ok(opts)
+    }
+}
+
+/// More workers than this is a typo rather than a machine
+const MAX_THREADS: usize = 4096;
+
+fn parse_threads(name: &str, value: &str) -> Result<usize, String> {
+    match parse_num(name, value)? {
+        n @ 1..=MAX_THREADS => Ok(n),
+        _ => Err(format!("{name} must be between 1 and {MAX_THREADS}")),
+    }
+}
+
+fn parse_num<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {flag}: {value}"))
+}
@@ -0,0 +1,102 @@
+//! Text interchange format of each station's sufficient statistics, so results
+//! of separate runs can be combined exactly with `--merge-partials`, which the
+//! rounded mean of the text format doesn't allow.
+//!
+//! One `station;min;max;sum;count` line per station, values in degrees with a
+//! single decimal. The fields are read from the end of the line, so a name
+//! may hold `;` itself, as under another `--delim` or `--pivot`.
+
+use std::{
+    fs,
+    io::{self, Write},
+};
+
+use crate::{
+    fmt_tenths, hash_name, map_file, parse_value,
+    sink::{ResultSink, StationResult},
+    Data, LookupTable,
+};
+
+pub struct PartialSink<W: Write> {
+    w: W,
+}
+
+impl<W: Write> PartialSink<W> {
+    pub fn new(w: W) -> Self {
+        PartialSink { w }
+    }
+}
+
+impl<W: Write> ResultSink for PartialSink<W> {
+    fn begin(&mut self, _stations: usize) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn write_station(&mut self, station: &StationResult) -> io::Result<()> {
+        let data = &station.data;
+        self.w.write_all(station.name)?;
+        writeln!(
+            self.w,
+            ";{};{};{};{}",
+            fmt_tenths(data.min as i64),
+            fmt_tenths(data.max as i64),
+            fmt_tenths(data.sum),
+            data.count
+        )
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.w.flush()
+    }
+}
+
+/// Merges the partial results in the file at `path` into `store`. A station
+/// on several lines, as in concatenated partial files, adds up.
+pub fn read(path: &str, store: &mut LookupTable) -> io::Result<()> {
+    let data = map_file(&fs::File::open(path)?)?;
+
+    let mut local_store = LookupTable::new();
+    for (idx, line) in data.split(|&ch| ch == b'\n').enumerate() {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.is_empty() {
+            continue;
+        }
+
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: expected station;min;max;sum;count", idx + 1),
+            )
+        };
+
+        let mut fields = line.rsplitn(5, |&ch| ch == b';');
+        let (Some(count), Some(sum), Some(max), Some(min), Some(name)) = (
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+        ) else {
+            return Err(invalid());
+        };
+        let count = std::str::from_utf8(count)
+            .ok()
+            .and_then(|count| count.parse().ok())
+            .ok_or_else(invalid)?;
+        let extreme = |val: &[u8]| i16::try_from(parse_value(val.iter().copied()));
+        let (Ok(min), Ok(max)) = (extreme(min), extreme(max)) else {
+            return Err(invalid());
+        };
+
+        let data = Data {
+            min,
+            max,
+            sum: parse_value(sum.iter().copied()),
+            count,
+        };
+        local_store.entry(name, hash_name(name)).merge(&data);
+    }
+
+    store.merge_into(local_store);
+    Ok(())
+}
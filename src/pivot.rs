@@ -0,0 +1,48 @@
+//! Rows of the `--pivot` output: each key's aggregate over all of its
+//! subkeys, followed by the aggregate of every `key/subkey`.
+
+use std::collections::BTreeMap;
+
+use crate::Data;
+
+type Group<'a> = (Data, Vec<(&'a [u8], Data)>);
+
+/// Expands a store keyed by the composite `key;subkey`, split at `delim`,
+/// into pivot rows sorted by key, then subkey.
+pub fn rows(store: &[(&[u8], Data, u32)], delim: u8) -> Vec<(Vec<u8>, Data)> {
+    let mut groups = BTreeMap::<&[u8], Group>::new();
+    for &(name, data, _) in store {
+        let delim = name
+            .iter()
+            .position(|&ch| ch == delim)
+            .unwrap_or(name.len());
+        let (key, subkey) = (&name[..delim], name.get(delim + 1..).unwrap_or_default());
+
+        let (total, subkeys) = groups.entry(key).or_insert((
+            Data {
+                min: data.min,
+                max: data.max,
+                sum: 0,
+                count: 0,
+            },
+            Vec::new(),
+        ));
+        total.min = total.min.min(data.min);
+        total.max = total.max.max(data.max);
+        total.sum += data.sum;
+        total.count += data.count;
+        subkeys.push((subkey, data));
+    }
+
+    let mut rows = Vec::new();
+    for (key, (total, mut subkeys)) in groups {
+        subkeys.sort_unstable_by_key(|(subkey, _)| *subkey);
+
+        rows.push((key.to_vec(), total));
+        for (subkey, data) in subkeys {
+            rows.push(([key, b"/", subkey].concat(), data));
+        }
+    }
+
+    rows
+}
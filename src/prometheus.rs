@@ -0,0 +1,107 @@
+//! Prometheus text exposition of the results: per station gauges labelled by
+//! the station name, plus the totals over all stations.
+
+use std::io::{self, Write};
+
+use crate::{
+    decode_name, fmt_tenths,
+    options::Options,
+    sink::{fmt_mean, ResultSink, StationResult},
+};
+
+const STATION_METRICS: [(&str, &str); 4] = [
+    ("brc_station_min", "Lowest measurement of the station"),
+    ("brc_station_mean", "Mean measurement of the station"),
+    ("brc_station_max", "Highest measurement of the station"),
+    ("brc_station_count", "Number of measurements of the station"),
+];
+
+/// Samples of a metric family have to be consecutive, so they're collected
+/// per family and written out in `finish`.
+pub struct PrometheusSink<'a, W: Write> {
+    w: W,
+    opts: &'a Options,
+    samples: [String; STATION_METRICS.len()],
+    rows: u64,
+    stations: usize,
+}
+
+impl<'a, W: Write> PrometheusSink<'a, W> {
+    pub fn new(w: W, opts: &'a Options) -> Self {
+        PrometheusSink {
+            w,
+            opts,
+            samples: Default::default(),
+            rows: 0,
+            stations: 0,
+        }
+    }
+}
+
+impl<W: Write> ResultSink for PrometheusSink<'_, W> {
+    fn begin(&mut self, _stations: usize) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn write_station(&mut self, station: &StationResult) -> io::Result<()> {
+        let data = &station.data;
+        let label = escape_label(&decode_name(station.name, &self.opts.encoding));
+        // A seeded station without values has no extremes nor mean
+        let values = match data.count {
+            0 => ["NaN".to_string(), "NaN".to_string(), "NaN".to_string()],
+            _ => [
+                fmt_tenths(data.min as i64),
+                fmt_mean(data, self.opts.rounding),
+                fmt_tenths(data.max as i64),
+            ],
+        };
+        let values = values.into_iter().chain([data.count.to_string()]);
+
+        for ((metric, _), (samples, value)) in STATION_METRICS
+            .iter()
+            .zip(self.samples.iter_mut().zip(values))
+        {
+            *samples += &format!("{metric}{{station=\"{label}\"}} {value}\n");
+        }
+        self.rows += data.count as u64;
+        self.stations += 1;
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        for ((metric, help), samples) in STATION_METRICS.iter().zip(&self.samples) {
+            writeln!(self.w, "# HELP {metric} {help}")?;
+            writeln!(self.w, "# TYPE {metric} gauge")?;
+            self.w.write_all(samples.as_bytes())?;
+        }
+
+        writeln!(
+            self.w,
+            "# HELP brc_total_rows Number of measurements aggregated"
+        )?;
+        writeln!(self.w, "# TYPE brc_total_rows gauge")?;
+        writeln!(self.w, "brc_total_rows {}", self.rows)?;
+        writeln!(self.w, "# HELP brc_distinct_stations Number of stations")?;
+        writeln!(self.w, "# TYPE brc_distinct_stations gauge")?;
+        writeln!(self.w, "brc_distinct_stations {}", self.stations)?;
+
+        self.w.flush()
+    }
+}
+
+/// Escapes a label value: backslash, double quote and line feed are the only
+/// characters needing it.
+fn escape_label(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '\\' => escaped += "\\\\",
+            '"' => escaped += "\\\"",
+            '\n' => escaped += "\\n",
+            ch => escaped.push(ch),
+        }
+    }
+
+    escaped
+}
@@ -0,0 +1,95 @@
+//! Interactive queries against the aggregated results, for `--repl`, so a
+//! large input is only read once however many questions are asked of it.
+//!
+//! Each input line is one of
+//! - `count`: the number of stations
+//! - `top <n>`: the `n` stations with the highest mean, highest first, of
+//!   those with any measurements
+//! - `quit`
+//! - a station name: its `min/mean/max` and number of measurements
+
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    io::{self, BufRead, Write},
+};
+
+use crate::{
+    decode_name, fmt_tenths,
+    options::{Encoding, Rounding},
+    sink::{fmt_mean, NO_VALUES},
+    Data,
+};
+
+const PROMPT: &str = "> ";
+
+pub fn run<R: BufRead, W: Write>(
+    input: R,
+    mut w: W,
+    stations: &[(&[u8], Data, u32)],
+    encoding: &Encoding,
+    rounding: Rounding,
+) -> io::Result<()> {
+    let by_name = stations
+        .iter()
+        .map(|(name, data, _)| (decode_name(name, encoding), data))
+        .collect::<HashMap<_, _>>();
+
+    // The prompt goes to stderr to keep the answers on stdout clean for scripts
+    eprint!("{PROMPT}");
+    for line in input.lines() {
+        let line = line?;
+        let query = line.trim();
+        match query.split_once(' ') {
+            _ if query.is_empty() => {}
+            _ if query == "quit" => break,
+            _ if query == "count" => writeln!(w, "{}", stations.len())?,
+            Some(("top", n)) => match n.trim().parse::<usize>() {
+                Ok(n) => {
+                    // Seeded stations without values have no mean to rank
+                    let mut hottest = stations
+                        .iter()
+                        .filter(|(_, data, _)| data.count > 0)
+                        .collect::<Vec<_>>();
+                    hottest.sort_by(|a, b| cmp_mean(&b.1, &a.1).then(a.0.cmp(b.0)));
+                    for (name, data, _) in hottest.into_iter().take(n) {
+                        let name = decode_name(name, encoding);
+                        writeln!(w, "{name}={}", summary(data, rounding))?;
+                    }
+                }
+                Err(_) => writeln!(w, "usage: top <n>")?,
+            },
+            _ => match by_name.get(query) {
+                Some(data) => {
+                    let summary = summary(data, rounding);
+                    writeln!(w, "{query}={summary} ({} measurements)", data.count)?
+                }
+                None => writeln!(w, "unknown station: {query}")?,
+            },
+        }
+        w.flush()?;
+        eprint!("{PROMPT}");
+    }
+
+    w.flush()
+}
+
+/// `min/mean/max` as printed by the text format, the mean rounded by
+/// `rounding`.
+fn summary(data: &Data, rounding: Rounding) -> String {
+    if data.count == 0 {
+        return NO_VALUES.to_string();
+    }
+
+    format!(
+        "{}/{}/{}",
+        fmt_tenths(data.min as i64),
+        fmt_mean(data, rounding),
+        fmt_tenths(data.max as i64)
+    )
+}
+
+/// Compares the exact means, `a.sum / a.count` against `b.sum / b.count`.
+fn cmp_mean(a: &Data, b: &Data) -> Ordering {
+    (a.sum as i128 * b.count as i128).cmp(&(b.sum as i128 * a.count as i128))
+}
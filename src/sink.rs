@@ -0,0 +1,213 @@
+//! Output formats, each a sink the sorted results are streamed into one
+//! station at a time.
+
+use std::io::{self, Write};
+
+use crate::{
+    conv_num, fmt_tenths, hash_name,
+    options::{Encoding, Options, Rounding},
+    write_name, Data, StationStats, Stats,
+};
+
+/// min/mean/max of a station seeded by `--seed-stations` without any values
+pub const NO_VALUES: &str = "NA/NA/NA";
+
+/// A station as it's handed to a [`ResultSink`]
+pub struct StationResult<'a> {
+    pub name: &'a [u8],
+    pub(crate) data: Data,
+    /// Index of the `LookupTable` slot the station was aggregated in
+    pub slot: u32,
+}
+
+impl StationResult<'_> {
+    /// The station's aggregate in degrees
+    pub fn stats(&self) -> StationStats {
+        self.data.stats()
+    }
+}
+
+/// An output format. The stations are written one at a time in the order
+/// they're printed in, between a `begin` and a `finish`.
+pub trait ResultSink {
+    /// Called once before the first station with the number of stations to
+    /// come.
+    fn begin(&mut self, stations: usize) -> io::Result<()>;
+    fn write_station(&mut self, station: &StationResult) -> io::Result<()>;
+    /// Called once after the last station, flushes the underlying writer.
+    fn finish(&mut self) -> io::Result<()>;
+}
+
+/// Streams `sorted_store` through `sink` from `begin` to `finish`.
+pub fn write_all(sink: &mut dyn ResultSink, sorted_store: &[(&[u8], Data, u32)]) -> io::Result<()> {
+    sink.begin(sorted_store.len())?;
+    for &(name, data, slot) in sorted_store {
+        sink.write_station(&StationResult { name, data, slot })?;
+    }
+    sink.finish()
+}
+
+/// `{name=min/mean/max, ...}`, with the timestamps of the extremes when
+/// `--timestamp-col` collected them, the slots under `--show-slots` and the
+/// checksums under `--station-checksums` and the `--percentiles` in brackets,
+/// e.g. `[p50=12.3 p99=40.1]`. The
+/// mean is rounded by `--rounding`, half up unless another mode is given.
+pub struct TextSink<'a, W: Write> {
+    w: W,
+    opts: &'a Options,
+    stats: &'a Stats,
+    /// Slots of the table the stations were aggregated in
+    table_size: u32,
+    first: bool,
+}
+
+impl<'a, W: Write> TextSink<'a, W> {
+    pub fn new(w: W, opts: &'a Options, stats: &'a Stats, table_size: usize) -> Self {
+        TextSink {
+            w,
+            opts,
+            stats,
+            table_size: table_size as u32,
+            first: true,
+        }
+    }
+}
+
+impl<W: Write> ResultSink for TextSink<'_, W> {
+    fn begin(&mut self, _stations: usize) -> io::Result<()> {
+        write!(self.w, "{{")
+    }
+
+    fn write_station(&mut self, station: &StationResult) -> io::Result<()> {
+        let (w, data) = (&mut self.w, &station.data);
+        if !std::mem::take(&mut self.first) {
+            write!(w, ", ")?;
+        }
+
+        let (min_ts, max_ts) = match self.stats.extremes.get(station.name) {
+            Some(e) => (e.min_ts, e.max_ts),
+            None => (&[][..], &[][..]),
+        };
+
+        let encoding = &self.opts.encoding;
+        write_name(&mut *w, station.name, encoding)?;
+        write!(w, "=")?;
+        if data.count == 0 {
+            write!(w, "{NO_VALUES}")?;
+        } else {
+            write!(w, "{:.1}", conv_num(data.min as i32))?;
+            if !min_ts.is_empty() {
+                write!(w, "@")?;
+                write_name(&mut *w, min_ts, encoding)?;
+            }
+            write!(w, "/{}", fmt_mean(data, self.opts.rounding))?;
+            write!(w, "/{:.1}", conv_num(data.max as i32))?;
+            if !max_ts.is_empty() {
+                write!(w, "@")?;
+                write_name(&mut *w, max_ts, encoding)?;
+            }
+        }
+        if !self.opts.percentiles.is_empty() {
+            let histogram = self.stats.histograms.get(station.name);
+            for (idx, &percentile) in self.opts.percentiles.iter().enumerate() {
+                let sep = if idx == 0 { " [" } else { " " };
+                match histogram {
+                    Some(histogram) => {
+                        let val = histogram.percentile(percentile);
+                        write!(w, "{sep}p{percentile}={:.1}", conv_num(val))?
+                    }
+                    None => write!(w, "{sep}p{percentile}=NA")?,
+                }
+            }
+            write!(w, "]")?;
+        }
+        if self.opts.show_slots {
+            let size = self.table_size;
+            let home = hash_name(station.name) % size;
+            let probe = (station.slot + size - home) % size;
+            write!(w, " (slot {}, probe {probe})", station.slot)?;
+        }
+        if self.opts.station_checksums {
+            let checksum = self.stats.checksums.get(station.name).copied();
+            write!(w, " #{:016x}", checksum.unwrap_or_default())?;
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        write!(self.w, "}}")?;
+        self.w.flush()
+    }
+}
+
+/// The mean with one decimal, rounded by `rounding` in whole tenths rather
+/// than by `{:.1}` of the float mean, which misses halves the float can't hold
+/// exactly.
+pub fn fmt_mean(data: &Data, rounding: Rounding) -> String {
+    fmt_tenths(round_mean(data.sum, data.count as i64, rounding))
+}
+
+/// Rounds `sum / count` to whole tenths.
+fn round_mean(sum: i64, count: i64, rounding: Rounding) -> i64 {
+    match rounding {
+        Rounding::Nearest => (2 * sum + count).div_euclid(2 * count),
+        Rounding::Ceil => -(-sum).div_euclid(count),
+        Rounding::Floor => sum.div_euclid(count),
+        Rounding::Trunc => sum / count,
+    }
+}
+
+/// Output of the Java reference implementation byte for byte: each statistic
+/// rounded half up to one decimal, names in UTF-16 order (the same as byte
+/// order for names within the Basic Multilingual Plane) and a trailing
+/// newline.
+pub struct ReferenceSink<'a, W: Write> {
+    w: W,
+    encoding: &'a Encoding,
+    first: bool,
+}
+
+impl<'a, W: Write> ReferenceSink<'a, W> {
+    pub fn new(w: W, encoding: &'a Encoding) -> Self {
+        ReferenceSink {
+            w,
+            encoding,
+            first: true,
+        }
+    }
+}
+
+impl<W: Write> ResultSink for ReferenceSink<'_, W> {
+    fn begin(&mut self, _stations: usize) -> io::Result<()> {
+        write!(self.w, "{{")
+    }
+
+    fn write_station(&mut self, station: &StationResult) -> io::Result<()> {
+        let data = &station.data;
+        if !std::mem::take(&mut self.first) {
+            write!(self.w, ", ")?;
+        }
+
+        write_name(&mut self.w, station.name, self.encoding)?;
+        if data.count == 0 {
+            return write!(self.w, "={NO_VALUES}");
+        }
+
+        // Math.round(sum / count * 10) / 10, kept in integer tenths
+        let mean = round_mean(data.sum, data.count as i64, Rounding::Nearest);
+
+        write!(
+            self.w,
+            "={}/{}/{}",
+            fmt_tenths(data.min as i64),
+            fmt_tenths(mean),
+            fmt_tenths(data.max as i64)
+        )
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        writeln!(self.w, "}}")?;
+        self.w.flush()
+    }
+}
@@ -0,0 +1,88 @@
+//! Slot storage of a `LookupTable`, allocated at a chosen alignment. At a
+//! cache line's alignment, with the slots' size dividing it, no slot
+//! straddles two lines.
+
+use std::{
+    alloc::{self, Layout},
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+};
+
+use crate::Slot;
+
+/// Length of a cache line on the CPUs the workers run on
+pub const CACHE_LINE: usize = 64;
+
+const _: () = assert!(CACHE_LINE.is_multiple_of(std::mem::size_of::<Slot>()));
+// Dropping `Slots` frees the memory without dropping the slots in it
+const _: () = assert!(!std::mem::needs_drop::<Slot>());
+
+/// An owned slice of empty slots, like a `Box<[Slot]>` with an alignment of
+/// its own.
+pub(crate) struct Slots {
+    ptr: NonNull<Slot>,
+    len: usize,
+    layout: Layout,
+}
+
+// SAFETY: owned like a `Box<[Slot]>`, which is `Send` and `Sync` as `Slot` is
+unsafe impl Send for Slots {}
+unsafe impl Sync for Slots {}
+
+impl Slots {
+    /// `len` empty slots, aligned to `align` or to the slots' own alignment
+    /// if that's larger.
+    pub fn new(len: usize, align: usize) -> Self {
+        let layout = Layout::array::<Slot>(len)
+            .and_then(|layout| layout.align_to(align))
+            .expect("slots too large to allocate");
+        if layout.size() == 0 {
+            return Slots {
+                ptr: NonNull::dangling(),
+                len,
+                layout,
+            };
+        }
+
+        // SAFETY: the layout has a nonzero size
+        let Some(ptr) = NonNull::new(unsafe { alloc::alloc(layout) }.cast::<Slot>()) else {
+            alloc::handle_alloc_error(layout);
+        };
+        for idx in 0..len {
+            // SAFETY: in bounds of the allocation just made
+            unsafe { ptr.add(idx).write(None) };
+        }
+
+        Slots { ptr, len, layout }
+    }
+
+    /// Alignment the slots were allocated at
+    pub fn align(&self) -> usize {
+        self.layout.align()
+    }
+}
+
+impl Deref for Slots {
+    type Target = [Slot];
+
+    fn deref(&self) -> &[Slot] {
+        // SAFETY: `len` initialized slots
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl DerefMut for Slots {
+    fn deref_mut(&mut self) -> &mut [Slot] {
+        // SAFETY: `len` initialized slots, borrowed uniquely through `self`
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Drop for Slots {
+    fn drop(&mut self) {
+        if self.layout.size() != 0 {
+            // SAFETY: allocated in `new` with this layout
+            unsafe { alloc::dealloc(self.ptr.as_ptr().cast(), self.layout) };
+        }
+    }
+}
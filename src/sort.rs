@@ -0,0 +1,90 @@
+//! MSD radix sort for large result sets, ordering keys exactly like the byte
+//! slice comparator does, and the keys of `--sort unicode`.
+
+/// Result counts above which the radix sort beats `sort_unstable_by_key`
+pub const RADIX_SORT_THRESHOLD: usize = 50_000;
+
+/// Buckets this small are finished off with a comparison sort
+const SMALL_BUCKET: usize = 32;
+
+pub fn radix_sort_by_key<T, K>(items: Vec<T>, key: K) -> Vec<T>
+where
+    K: Fn(&T) -> &[u8],
+{
+    let mut sorted = Vec::with_capacity(items.len());
+    msd(items, 0, &key, &mut sorted);
+    sorted
+}
+
+/// Sorts `items`, which all share their first `depth` key bytes, into `out`.
+fn msd<T, K>(mut items: Vec<T>, depth: usize, key: &K, out: &mut Vec<T>)
+where
+    K: Fn(&T) -> &[u8],
+{
+    if items.len() <= SMALL_BUCKET {
+        items.sort_unstable_by(|a, b| key(a)[depth..].cmp(&key(b)[depth..]));
+        out.extend(items);
+        return;
+    }
+
+    // Bucket 0 holds the keys ending at `depth`, which sort before any longer
+    // key sharing the prefix
+    let mut buckets = (0..257).map(|_| Vec::new()).collect::<Vec<_>>();
+    for item in items {
+        let bucket = key(&item).get(depth).map_or(0, |&ch| ch as usize + 1);
+        buckets[bucket].push(item);
+    }
+
+    let mut buckets = buckets.into_iter();
+    out.extend(buckets.next().unwrap());
+    for bucket in buckets.filter(|b| !b.is_empty()) {
+        msd(bucket, depth + 1, key, out);
+    }
+}
+
+/// Sort key of a name under `--sort unicode`: lowercase, with the accented
+/// Latin letters folded into their base letters, so `Ávila` sorts among the
+/// `a`s rather than after `Z`. Not a locale's collation, names folding alike
+/// are left to the caller to order.
+pub fn collation_key(name: &str) -> String {
+    let mut key = String::with_capacity(name.len());
+    for ch in name.chars() {
+        match fold_latin(ch) {
+            Some(base) => key.push_str(base),
+            None => key.extend(ch.to_lowercase()),
+        }
+    }
+
+    key
+}
+
+/// Base letters of the Latin-1 Supplement and Latin Extended-A letters
+fn fold_latin(ch: char) -> Option<&'static str> {
+    Some(match ch {
+        'À'..='Å' | 'à'..='å' | 'Ā'..='ą' => "a",
+        'Æ' | 'æ' => "ae",
+        'Ç' | 'ç' | 'Ć'..='č' => "c",
+        'Ð' | 'ð' | 'Ď'..='đ' => "d",
+        'È'..='Ë' | 'è'..='ë' | 'Ē'..='ě' => "e",
+        'Ĝ'..='ģ' => "g",
+        'Ĥ'..='ħ' => "h",
+        'Ì'..='Ï' | 'ì'..='ï' | 'Ĩ'..='ı' => "i",
+        'Ĳ' | 'ĳ' => "ij",
+        'Ĵ' | 'ĵ' => "j",
+        'Ķ'..='ĸ' => "k",
+        'Ĺ'..='ł' => "l",
+        'Ñ' | 'ñ' | 'Ń'..='ŋ' => "n",
+        'Ò'..='Ö' | 'Ø' | 'ò'..='ö' | 'ø' | 'Ō'..='ő' => "o",
+        'Œ' | 'œ' => "oe",
+        'Ŕ'..='ř' => "r",
+        'Ś'..='š' | 'ſ' => "s",
+        'ß' => "ss",
+        'Ţ'..='ŧ' => "t",
+        'Þ' | 'þ' => "th",
+        'Ù'..='Ü' | 'ù'..='ü' | 'Ũ'..='ų' => "u",
+        'Ŵ' | 'ŵ' => "w",
+        'Ý' | 'ý' | 'ÿ' | 'Ŷ'..='Ÿ' => "y",
+        'Ź'..='ž' => "z",
+        _ => return None,
+    })
+}
@@ -0,0 +1,76 @@
+//! SQLite database of the results, for `--format sqlite`, with a single table
+//!
+//! ```sql
+//! CREATE TABLE stations(name TEXT, min REAL, mean REAL, max REAL, count INTEGER)
+//! ```
+//!
+//! The database is built in memory with `rusqlite`, behind the `sqlite`
+//! feature, and written out whole once the last station is in, so it can go
+//! to stdout like the other formats. Seeded stations without values have
+//! NULL extremes and mean.
+
+use std::io::{self, Write};
+
+use rusqlite::{params, Connection, MAIN_DB};
+
+use crate::{
+    decode_name,
+    options::Encoding,
+    sink::{ResultSink, StationResult},
+};
+
+const SCHEMA: &str =
+    "CREATE TABLE stations(name TEXT, min REAL, mean REAL, max REAL, count INTEGER)";
+
+const INSERT: &str = "INSERT INTO stations VALUES (?1, ?2, ?3, ?4, ?5)";
+
+pub struct SqliteSink<'a, W: Write> {
+    w: W,
+    encoding: &'a Encoding,
+    db: Connection,
+}
+
+impl<'a, W: Write> SqliteSink<'a, W> {
+    pub fn new(w: W, encoding: &'a Encoding) -> io::Result<Self> {
+        Ok(SqliteSink {
+            w,
+            encoding,
+            db: Connection::open_in_memory().map_err(io::Error::other)?,
+        })
+    }
+}
+
+impl<W: Write> ResultSink for SqliteSink<'_, W> {
+    fn begin(&mut self, _stations: usize) -> io::Result<()> {
+        self.db
+            .execute_batch(&format!("{SCHEMA}; BEGIN"))
+            .map_err(io::Error::other)
+    }
+
+    fn write_station(&mut self, station: &StationResult) -> io::Result<()> {
+        let data = &station.data;
+        let name = decode_name(station.name, self.encoding);
+        let real = |val: f64| (data.count > 0).then_some(val);
+
+        let mut insert = self.db.prepare_cached(INSERT).map_err(io::Error::other)?;
+        insert
+            .execute(params![
+                name,
+                real(data.min as f64 / 10.),
+                real(data.mean()),
+                real(data.max as f64 / 10.),
+                data.count,
+            ])
+            .map_err(io::Error::other)?;
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.db.execute_batch("COMMIT").map_err(io::Error::other)?;
+        let bytes = self.db.serialize(MAIN_DB).map_err(io::Error::other)?;
+
+        self.w.write_all(&bytes)?;
+        self.w.flush()
+    }
+}
@@ -0,0 +1,62 @@
+//! The `--min-throughput` guardrail, which fails processing that has slowed
+//! down, e.g. reading from a degraded network filesystem.
+
+use std::time::{Duration, Instant};
+
+/// Source of the time elapsed since processing started, replaceable so the
+/// guard can be driven without waiting on a real clock.
+pub trait Clock {
+    fn elapsed(&self) -> Duration;
+}
+
+/// Wall clock time since [`SystemClock::start`]
+pub struct SystemClock(Instant);
+
+impl SystemClock {
+    pub fn start() -> Self {
+        SystemClock(Instant::now())
+    }
+}
+
+impl Clock for SystemClock {
+    fn elapsed(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
+
+/// Checks the average throughput since the clock started against a minimum,
+/// past a warmup during which it isn't enforced.
+pub struct ThroughputGuard<C: Clock> {
+    clock: C,
+    min_gbps: f64,
+    warmup: Duration,
+}
+
+impl<C: Clock> ThroughputGuard<C> {
+    pub fn new(clock: C, min_gbps: f64, warmup: Duration) -> Self {
+        ThroughputGuard {
+            clock,
+            min_gbps,
+            warmup,
+        }
+    }
+
+    /// Fails once the warmup is over if fewer than `min_gbps` gigabytes per
+    /// second were processed on average.
+    pub fn check(&self, processed: u64) -> Result<(), String> {
+        let elapsed = self.clock.elapsed();
+        if elapsed < self.warmup {
+            return Ok(());
+        }
+
+        let gbps = processed as f64 / elapsed.as_secs_f64() / 1e9;
+        if gbps < self.min_gbps {
+            return Err(format!(
+                "throughput of {gbps:.3} GB/s is below the minimum of {} GB/s",
+                self.min_gbps
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -1,37 +1,158 @@
+#[cfg(any(target_os = "linux", target_os = "macos"))]
 use std::ffi::c_int;
+#[cfg(target_os = "linux")]
+use std::ffi::c_ulong;
+#[cfg(windows)]
+use std::ffi::c_void;
+
+#[cfg(target_os = "linux")]
+const EINVAL: i32 = 22;
 
-#[repr(C)]
-struct cpu_set_t {
-    #[cfg(all(target_pointer_width = "32", not(target_arch = "x86_64")))]
-    bits: [u32; 32],
-    #[cfg(not(all(target_pointer_width = "32", not(target_arch = "x86_64"))))]
-    bits: [u64; 16],
+// CPU masks are sized in bytes like glibc's `CPU_ALLOC_SIZE`, as a fixed
+// `cpu_set_t` only holds the first 1024 CPUs
+#[cfg(target_os = "linux")]
+extern "C" {
+    fn sched_setaffinity(pid: i32, cpusetsize: usize, cpuset: *const c_ulong) -> c_int;
+    fn sched_getaffinity(pid: i32, cpusetsize: usize, cpuset: *mut c_ulong) -> c_int;
 }
 
+#[cfg(target_os = "macos")]
 extern "C" {
-    fn sched_setaffinity(pid: i32, cpusetsize: usize, cpuset: *const cpu_set_t) -> c_int;
+    fn pthread_self() -> usize;
+    fn pthread_mach_thread_np(thread: usize) -> u32;
+    fn thread_policy_set(thread: u32, flavor: u32, policy_info: *mut c_int, count: u32) -> c_int;
 }
 
-fn CPU_SET(cpu: usize, cpuset: &mut cpu_set_t) {
-    let size_in_bits = 8 * std::mem::size_of_val(&cpuset.bits[0]); // 32, 64 etc
-    let (idx, offset) = (cpu / size_in_bits, cpu % size_in_bits);
-    cpuset.bits[idx] |= 1 << offset;
+#[cfg(windows)]
+extern "system" {
+    fn GetCurrentThread() -> *mut c_void;
+    /// Returns the previous mask, or 0 on failure
+    fn SetThreadAffinityMask(thread: *mut c_void, mask: usize) -> usize;
 }
 
 #[cfg(target_os = "linux")]
 #[inline(always)]
 pub fn set_cpu_affinity(id: usize) -> bool {
-    let mut cpuset = unsafe { std::mem::zeroed::<cpu_set_t>() };
-
-    unsafe { CPU_SET(id, &mut cpuset) };
+    // Sized for `id` like glibc's `CPU_ALLOC`, a `cpu_set_t` would be out of
+    // bounds past CPU 1023
+    let word_bits = c_ulong::BITS as usize;
+    let mut cpuset = vec![0 as c_ulong; id / word_bits + 1];
+    cpuset[id / word_bits] |= 1 << (id % word_bits);
 
-    let res = unsafe { sched_setaffinity(0, std::mem::size_of::<cpu_set_t>(), &cpuset) };
+    let res = unsafe { sched_setaffinity(0, std::mem::size_of_val(&cpuset[..]), cpuset.as_ptr()) };
 
     res == 0
 }
 
+/// macOS can't pin threads to CPUs. Instead the thread gets the affinity tag
+/// `id + 1` (0 is no tag), a hint that the scheduler should keep threads of
+/// different tags on different cores, which it may ignore. Apple silicon
+/// doesn't support affinity tags at all and returns `false`.
 #[cfg(target_os = "macos")]
 #[inline(always)]
 pub fn set_cpu_affinity(id: usize) -> bool {
-    false
+    const THREAD_AFFINITY_POLICY: u32 = 4;
+    const THREAD_AFFINITY_POLICY_COUNT: u32 = 1;
+    const KERN_SUCCESS: c_int = 0;
+
+    let Ok(mut tag) = c_int::try_from(id + 1) else {
+        return false;
+    };
+
+    let res = unsafe {
+        thread_policy_set(
+            pthread_mach_thread_np(pthread_self()),
+            THREAD_AFFINITY_POLICY,
+            &mut tag,
+            THREAD_AFFINITY_POLICY_COUNT,
+        )
+    };
+
+    res == KERN_SUCCESS
+}
+
+/// Sets the affinity mask of the calling thread, returning the previous one.
+/// Only the first processor group's CPUs can be addressed by a mask.
+#[cfg(windows)]
+fn set_thread_mask(id: usize) -> Option<usize> {
+    if id >= usize::BITS as usize {
+        return None;
+    }
+
+    match unsafe { SetThreadAffinityMask(GetCurrentThread(), 1 << id) } {
+        0 => None,
+        previous => Some(previous),
+    }
+}
+
+#[cfg(windows)]
+#[inline(always)]
+pub fn set_cpu_affinity(id: usize) -> bool {
+    set_thread_mask(id).is_some()
+}
+
+/// Restores the CPU affinity the thread had before [`pin_current_thread`]
+/// when dropped.
+pub struct AffinityGuard {
+    #[cfg(target_os = "linux")]
+    previous: Vec<c_ulong>,
+    #[cfg(windows)]
+    previous: usize,
+}
+
+/// The calling thread's CPU mask. The kernel rejects masks smaller than the
+/// CPUs it supports, so the mask doubles from 1024 CPUs until one fits.
+#[cfg(target_os = "linux")]
+fn current_cpu_mask() -> Option<Vec<c_ulong>> {
+    const MAX_CPUS: usize = 1 << 20;
+
+    let word_bits = c_ulong::BITS as usize;
+    let mut cpus = 1024;
+    while cpus <= MAX_CPUS {
+        let mut mask = vec![0 as c_ulong; cpus / word_bits];
+        let size = std::mem::size_of_val(&mask[..]);
+        if unsafe { sched_getaffinity(0, size, mask.as_mut_ptr()) } == 0 {
+            return Some(mask);
+        }
+        if std::io::Error::last_os_error().raw_os_error() != Some(EINVAL) {
+            return None;
+        }
+        cpus *= 2;
+    }
+
+    None
+}
+
+/// Pins the calling thread to CPU `id` until the returned guard is dropped.
+#[cfg(target_os = "linux")]
+pub fn pin_current_thread(id: usize) -> Option<AffinityGuard> {
+    let previous = current_cpu_mask()?;
+
+    set_cpu_affinity(id).then_some(AffinityGuard { previous })
+}
+
+#[cfg(windows)]
+pub fn pin_current_thread(id: usize) -> Option<AffinityGuard> {
+    set_thread_mask(id).map(|previous| AffinityGuard { previous })
+}
+
+/// Affinity tags are only hints on macOS, so the merge thread isn't tagged.
+#[cfg(target_os = "macos")]
+pub fn pin_current_thread(_id: usize) -> Option<AffinityGuard> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for AffinityGuard {
+    fn drop(&mut self) {
+        let size = std::mem::size_of_val(&self.previous[..]);
+        unsafe { sched_setaffinity(0, size, self.previous.as_ptr()) };
+    }
+}
+
+#[cfg(windows)]
+impl Drop for AffinityGuard {
+    fn drop(&mut self) {
+        unsafe { SetThreadAffinityMask(GetCurrentThread(), self.previous) };
+    }
 }
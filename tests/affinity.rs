@@ -0,0 +1,77 @@
+use std::{cell::RefCell, fs, path::Path, process::Command, rc::Rc};
+
+use brc_rust::affinity::{self, Affinity};
+
+/// Records the pinning and restoring of the thread's affinity, pinning to
+/// `usable` CPUs only
+struct Recorder {
+    calls: Rc<RefCell<Vec<String>>>,
+    usable: usize,
+}
+
+struct Restore(Rc<RefCell<Vec<String>>>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        self.0.borrow_mut().push("restore".to_string());
+    }
+}
+
+impl Affinity for Recorder {
+    type Guard = Restore;
+
+    fn pin_current_thread(&self, cpu: usize) -> Option<Restore> {
+        self.calls.borrow_mut().push(format!("pin {cpu}"));
+        (cpu < self.usable).then(|| Restore(self.calls.clone()))
+    }
+}
+
+fn merge_pinned_to(cpu: Option<usize>) -> Vec<String> {
+    let calls = Rc::new(RefCell::new(Vec::new()));
+    let recorder = Recorder {
+        calls: calls.clone(),
+        usable: 4,
+    };
+
+    let merged = affinity::pinned(&recorder, cpu, || {
+        calls.borrow_mut().push("merge".to_string());
+        42
+    });
+    assert_eq!(merged, 42);
+
+    calls.take()
+}
+
+#[test]
+fn affinity_is_restored_after_the_merge() {
+    assert_eq!(merge_pinned_to(Some(3)), ["pin 3", "merge", "restore"]);
+}
+
+#[test]
+fn merge_runs_unpinned_without_a_cpu() {
+    assert_eq!(merge_pinned_to(None), ["merge"]);
+}
+
+#[test]
+fn merge_runs_unpinned_when_pinning_fails() {
+    assert_eq!(merge_pinned_to(Some(100)), ["pin 100", "merge"]);
+}
+
+/// CPUs past the 1024 a fixed `cpu_set_t` holds are pinned to or fail
+/// cleanly, the merge carries on either way
+#[test]
+fn pinning_past_cpu_1024_falls_back() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let golden = fs::read(fixtures.join("measurements.golden")).unwrap();
+
+    for cpu in ["1024", "5000"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+            .args(["--small-file-threshold", "0", "--pin-merge", cpu])
+            .arg(fixtures.join("measurements.txt"))
+            .output()
+            .expect("failed to run brc-rust");
+        assert!(output.status.success(), "--pin-merge {cpu}");
+
+        assert_eq!(output.stdout, golden, "--pin-merge {cpu}");
+    }
+}
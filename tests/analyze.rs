@@ -0,0 +1,37 @@
+use std::{fs, process::Command};
+
+fn run(args: &[&str]) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(
+        output.status.success(),
+        "brc-rust exited with {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    output.stdout
+}
+
+/// 90 lines of 5 bytes, 9 of 10 and one of 100, the last without a newline
+#[test]
+fn analyze_reports_known_line_lengths() {
+    let mut input = "A;1.0\n".repeat(90) + &"Abcde;10.0\n".repeat(9);
+    input += &format!("{};1.0", "x".repeat(96));
+    let path = std::env::temp_dir().join(format!("brc-analyze-{}.txt", std::process::id()));
+    fs::write(&path, input).unwrap();
+
+    let output = run(&["--analyze", path.to_str().unwrap()]);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&output),
+        "lines: 100\n\
+         line length: min=5 max=100 mean=6.4\n\
+         line length p50: 5\n\
+         line length p90: 5\n\
+         line length p99: 10\n\
+         line length p99.9: 100\n"
+    );
+}
@@ -0,0 +1,94 @@
+use std::{env, fs, path::PathBuf, process::Command};
+
+const STATIONS: &[&str] = &["Abha", "Baku", "São Paulo", "Zürich"];
+
+fn temp_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("brc-binary-{}-{name}", std::process::id()))
+}
+
+/// Encodes the records in the layout described in `binary.rs`.
+fn encode(records: &[(u16, i16)]) -> Vec<u8> {
+    let mut out = b"BRCB".to_vec();
+    out.extend((STATIONS.len() as u16).to_le_bytes());
+    for name in STATIONS {
+        out.push(name.len() as u8);
+        out.extend(name.as_bytes());
+    }
+    for (id, val) in records {
+        out.extend(id.to_le_bytes());
+        out.extend(val.to_le_bytes());
+    }
+
+    out
+}
+
+fn run(args: &[&str], input: &[u8], name: &str) -> std::process::Output {
+    let path = temp_path(name);
+    fs::write(&path, input).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .arg(&path)
+        .output()
+        .expect("failed to run brc-rust");
+    fs::remove_file(&path).unwrap();
+
+    output
+}
+
+#[test]
+fn binary_input_matches_its_text_equivalent() {
+    let records = (0..1000u16)
+        .map(|i| {
+            (
+                i * 7 % STATIONS.len() as u16,
+                ((i as i32 * 37) % 1999 - 999) as i16,
+            )
+        })
+        .collect::<Vec<_>>();
+    let text = records
+        .iter()
+        .map(|&(id, val)| {
+            let sign = if val < 0 { "-" } else { "" };
+            let val = val.unsigned_abs();
+            format!(
+                "{};{sign}{}.{}\n",
+                STATIONS[id as usize],
+                val / 10,
+                val % 10
+            )
+        })
+        .collect::<String>();
+
+    let expected = run(&[], text.as_bytes(), "text");
+    assert!(expected.status.success());
+
+    for threads in ["1", "3"] {
+        let args = [
+            "--input-format",
+            "binary",
+            "--small-file-threshold",
+            "0",
+            "--threads",
+            threads,
+        ];
+        let output = run(&args, &encode(&records), "bin");
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&expected.stdout),
+            "{threads} threads"
+        );
+    }
+}
+
+#[test]
+fn unknown_name_id_is_an_error() {
+    let output = run(
+        &["--input-format", "binary"],
+        &encode(&[(0, 10), (STATIONS.len() as u16, 20)]),
+        "unknown",
+    );
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown name id 4"));
+}
@@ -0,0 +1,70 @@
+use std::{env, fs, path::Path, process::Command};
+
+fn fixture(name: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+fn run(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .output()
+        .expect("failed to run brc-rust")
+}
+
+#[test]
+fn matching_reference_passes() {
+    let golden = fs::read_to_string(fixture("measurements.golden")).unwrap();
+
+    let reference = fixture("measurements.golden");
+    let output = run(&["--check", &reference, &fixture("measurements.txt")]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), golden);
+}
+
+#[test]
+fn mismatching_reference_reports_the_first_station() {
+    let golden = fs::read_to_string(fixture("measurements.golden")).unwrap();
+    let path = env::temp_dir().join(format!("brc-check-{}.txt", std::process::id()));
+    fs::write(
+        &path,
+        golden.replace("Accra=-63.5/16.6/98.0", "Accra=-63.5/16.7/98.0"),
+    )
+    .unwrap();
+
+    let output = run(&[
+        &format!("--check={}", path.display()),
+        &fixture("measurements.txt"),
+    ]);
+    fs::remove_file(&path).unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("at entry 3\n- Accra=-63.5/16.7/98.0\n+ Accra=-63.5/16.6/98.0"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn missing_station_is_reported() {
+    let path = env::temp_dir().join(format!("brc-check-missing-{}.txt", std::process::id()));
+    fs::write(&path, "{A=0.1/0.2/0.2, B=-0.2/-0.1/-0.1}").unwrap();
+
+    let reference = path.to_str().unwrap();
+    let output = run(&["--check", reference, &fixture("rounding.txt")]);
+    fs::remove_file(&path).unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("at entry 3\n- (nothing)\n+ C=1.0/1.1/1.1"),
+        "{stderr}"
+    );
+}
@@ -0,0 +1,49 @@
+use std::{env, fs, process::Command};
+
+fn run(name: &str, input: &str, args: &[&str]) -> String {
+    let path = env::temp_dir().join(format!("brc-checksums-{}-{name}.txt", std::process::id()));
+    fs::write(&path, input).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .arg("--station-checksums")
+        .args(args)
+        .arg(&path)
+        .output()
+        .expect("failed to run brc-rust");
+    fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Splits `{name=min/mean/max #checksum}` into the aggregates and checksum.
+fn split(output: String) -> (String, String) {
+    let (aggregates, checksum) = output
+        .trim_matches(['{', '}'])
+        .split_once(" #")
+        .unwrap_or_else(|| panic!("no checksum in {output}"));
+
+    (aggregates.to_owned(), checksum.to_owned())
+}
+
+#[test]
+fn same_aggregates_of_different_values_differ_in_checksum() {
+    // Same min, max, sum and count
+    let (first, first_checksum) = split(run("a", "A;1.0\nA;1.5\nA;2.5\nA;3.0\n", &[]));
+    let (second, second_checksum) = split(run("b", "A;1.0\nA;2.0\nA;2.0\nA;3.0\n", &[]));
+
+    assert_eq!(first, "A=1.0/2.0/3.0");
+    assert_eq!(first, second);
+    assert_ne!(first_checksum, second_checksum);
+}
+
+#[test]
+fn checksum_ignores_line_order_and_workers() {
+    let (_, checksum) = split(run("c", "A;1.0\nA;1.5\nA;2.5\nA;3.0\n", &[]));
+    let (_, shuffled) = split(run(
+        "d",
+        "A;2.5\nA;1.0\nA;3.0\nA;1.5\n",
+        &["--small-file-threshold", "0", "--threads", "3"],
+    ));
+
+    assert_eq!(checksum, shuffled);
+}
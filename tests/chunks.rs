@@ -0,0 +1,312 @@
+use std::{
+    env, fs,
+    path::Path,
+    process::{Command, Output},
+};
+
+/// Lines of very different lengths, so with many workers some get a region
+/// lying entirely within a single line and boundaries fall on every byte of
+/// a line: its start, its newline and everything in between.
+fn generate(seed: u64, lines: usize) -> String {
+    let mut state = seed.wrapping_mul(0x9e3779b97f4a7c15) | 1;
+    let mut next = || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 33) as usize
+    };
+
+    let mut input = (0..lines)
+        .map(|idx| {
+            let name = match next() % 4 {
+                0 => "x".repeat(1 + next() % 300),
+                1 => format!("S{}", idx % 3),
+                2 => "Zürich".to_string(),
+                _ => "ø".repeat(1 + next() % 40),
+            };
+            let eol = if next() % 5 == 0 { "\r\n" } else { "\n" };
+            format!("{name};{}.{}{eol}", next() % 100, next() % 10)
+        })
+        .collect::<String>();
+    // Some files end on a line without its newline
+    if seed % 2 == 1 {
+        input.pop();
+    }
+
+    input
+}
+
+#[test]
+fn chunk_bounds_are_whole_lines_covering_the_file() {
+    let mut inputs = (0..40)
+        .map(|seed| generate(seed, 1 + seed as usize % 13))
+        .collect::<Vec<_>>();
+    inputs.extend(["".to_string(), "\n".to_string(), "Abha;1.0".to_string()]);
+
+    for input in &inputs {
+        let data = input.as_bytes();
+        for threads in [1, 2, 3, 7, 64] {
+            let bounds = brc_rust::chunk_bounds(data, threads);
+            assert_eq!(bounds.len(), threads, "{threads} threads:\n{input}");
+
+            let mut next = 0;
+            for &(start, end) in &bounds {
+                assert_eq!(start, next, "{bounds:?}:\n{input}");
+                assert!(start <= end, "{bounds:?}:\n{input}");
+                assert!(
+                    start == 0 || start == data.len() || data[start - 1] == b'\n',
+                    "{start} in {bounds:?} isn't a line start:\n{input}"
+                );
+                next = end;
+            }
+            assert_eq!(next, data.len(), "{bounds:?}:\n{input}");
+        }
+    }
+}
+
+fn fixture(name: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn run(args: &[&str], path: &Path) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .arg(path)
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(output.status.success());
+    output
+}
+
+#[test]
+fn every_line_is_counted_once_with_more_workers_than_lines() {
+    for seed in 0..40 {
+        let lines = 1 + seed as usize % 13;
+        let input = generate(seed, lines);
+        let path = env::temp_dir().join(format!("brc-chunks-{}-{seed}.txt", std::process::id()));
+        fs::write(&path, &input).unwrap();
+
+        for threads in [2, 3, 7, lines, lines + 1, 64] {
+            let threads = threads.to_string();
+            let args = [
+                "--verbose",
+                "--small-file-threshold",
+                "0",
+                "--threads",
+                &threads,
+            ];
+            let stderr = String::from_utf8(run(&args, &path).stderr).unwrap();
+            assert!(
+                stderr.contains(&format!("lines: {lines}, ")),
+                "seed {seed}, {threads} threads: {stderr}\n{input}"
+            );
+        }
+        fs::remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+fn many_workers_match_a_single_pass() {
+    let path = env::temp_dir().join(format!("brc-chunks-{}.txt", std::process::id()));
+    fs::write(&path, generate(7, 200)).unwrap();
+
+    let single = run(&["--single-thread"], &path).stdout;
+    for threads in ["150", "199", "200", "256"] {
+        let output = run(
+            &["--small-file-threshold", "0", "--threads", threads],
+            &path,
+        )
+        .stdout;
+        assert_eq!(
+            String::from_utf8_lossy(&output),
+            String::from_utf8_lossy(&single),
+            "{threads} threads"
+        );
+    }
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn chunk_sizes_differ_by_at_most_one_line() {
+    let input = fs::read(fixture("measurements.txt")).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args([
+            "--small-file-threshold",
+            "0",
+            "--threads",
+            "7",
+            "--dump-partials",
+        ])
+        .arg(fixture("measurements.txt"))
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(output.status.success());
+
+    let ranges = String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .filter_map(|line| line.split_once(": bytes "))
+        .map(|(_, rest)| {
+            let (start, end) = rest.split_once(',').unwrap().0.split_once("..").unwrap();
+            (
+                start.parse::<usize>().unwrap(),
+                end.parse::<usize>().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(ranges.len(), 7);
+
+    // The even split differs by at most a byte, snapping its ends to line
+    // starts moves each by less than a line
+    let longest = input
+        .split(|&ch| ch == b'\n')
+        .map(<[u8]>::len)
+        .max()
+        .unwrap();
+    let sizes = ranges.iter().map(|(start, end)| end - start);
+    assert!(sizes.clone().max().unwrap() - sizes.min().unwrap() <= 2 * longest + 1);
+    assert!(ranges.windows(2).all(|w| w[0].1 == w[1].0));
+    assert!(ranges
+        .iter()
+        .all(|&(start, _)| start == 0 || input[start - 1] == b'\n'));
+    assert_eq!((ranges[0].0, ranges.last().unwrap().1), (0, input.len()));
+}
+
+/// A header isn't parsed by any worker, the first range starts after it.
+#[test]
+fn dumped_ranges_start_after_the_header() {
+    let input = fs::read_to_string(fixture("measurements.txt")).unwrap();
+    let header = "# recorded hourly\n";
+    let path = std::env::temp_dir().join(format!("brc-dump-header-{}.txt", std::process::id()));
+    fs::write(&path, header.to_string() + &input).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args([
+            "--small-file-threshold",
+            "0",
+            "--threads",
+            "3",
+            "--dump-partials",
+        ])
+        .arg(&path)
+        .output()
+        .expect("failed to run brc-rust");
+    fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.starts_with(&format!("worker 0: bytes {}..", header.len())),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn explained_ranges_match_the_workers() {
+    let path = fixture("measurements.txt");
+    let input = fs::read(&path).unwrap();
+    let args = ["--small-file-threshold", "0", "--threads", "5"];
+
+    let output = run(&[&args[..], &["--explain"]].concat(), Path::new(&path));
+    let output = String::from_utf8_lossy(&output.stdout);
+    let mut lines = output.lines();
+    assert_eq!(lines.next(), Some("5 workers"));
+    let ranges = lines
+        .map(|line| {
+            let range = line
+                .split_once(": bytes ")
+                .unwrap()
+                .1
+                .split_once(',')
+                .unwrap()
+                .0;
+            let (start, end) = range.split_once("..").unwrap();
+            (
+                start.parse::<usize>().unwrap(),
+                end.parse::<usize>().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let dumped = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .args(["--dump-partials", &path])
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(dumped.status.success());
+    let records = String::from_utf8_lossy(&dumped.stderr)
+        .lines()
+        .filter_map(|line| line.strip_suffix(" records"))
+        .map(|line| line.rsplit(' ').next().unwrap().parse::<usize>().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(ranges.len(), records.len());
+    assert_eq!((ranges[0].0, ranges.last().unwrap().1), (0, input.len()));
+    for (idx, (&(start, end), records)) in ranges.iter().zip(records).enumerate() {
+        assert!(start == 0 || input[start - 1] == b'\n', "worker {idx}");
+        let lines = input[start..end].iter().filter(|&&ch| ch == b'\n').count();
+        assert_eq!(lines, records, "worker {idx}");
+    }
+}
+
+/// A single worker's range is the whole file, its last line included.
+#[test]
+fn single_worker_covers_the_whole_file() {
+    let golden = fs::read(fixture("measurements.golden")).unwrap();
+    let input = fs::read(fixture("measurements.txt")).unwrap();
+    assert_eq!(brc_rust::chunk_bounds(&input, 1), [(0, input.len())]);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(["--small-file-threshold", "0", "--threads", "1"])
+        .args(["--dump-partials", &fixture("measurements.txt")])
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(output.status.success());
+
+    assert_eq!(output.stdout, golden);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        format!(
+            "worker 0: bytes 0..{}, 16 stations, 204 records\n",
+            input.len()
+        )
+    );
+}
+
+#[test]
+fn dumped_record_counts_sum_to_the_total() {
+    for merge in ["--pin-merge=0", "--streaming-merge"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+            .args(["--small-file-threshold", "0", "--threads", "5", "--verbose"])
+            .args(["--dump-partials", merge, &fixture("measurements.txt")])
+            .output()
+            .expect("failed to run brc-rust");
+        assert!(output.status.success(), "{merge}");
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let partials = stderr
+            .lines()
+            .filter(|line| line.starts_with("worker "))
+            .map(|line| {
+                let mut fields = line.split(", ").skip(1);
+                let mut count = |unit: &str| {
+                    let field = fields.next().unwrap();
+                    field.strip_suffix(unit).unwrap().parse::<u32>().unwrap()
+                };
+                (count(" stations"), count(" records"))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(partials.len(), 5, "{merge}: {stderr}");
+
+        let records = partials.iter().map(|(_, records)| records).sum::<u32>();
+        assert_eq!(records, 204, "{merge}: {stderr}");
+        assert!(
+            partials.iter().all(|&(stations, _)| stations <= 16),
+            "{stderr}"
+        );
+        assert!(stderr.contains("lines: 204, "), "{merge}: {stderr}");
+    }
+}
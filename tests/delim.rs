@@ -0,0 +1,54 @@
+use std::{env, fs, path::Path, process::Command};
+
+fn run(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(
+        output.status.success(),
+        "brc-rust exited with {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// The same measurements separated by `;`, a tab and `,` aggregate the same,
+/// on the fast path and on the loose one `--thousands-sep` takes.
+#[test]
+fn delimiters_aggregate_identically() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let input = fs::read_to_string(fixtures.join("measurements.txt")).unwrap();
+    let golden = fs::read_to_string(fixtures.join("measurements.golden")).unwrap();
+
+    for (delim, flag) in [
+        (';', "--delim=;"),
+        ('\t', "--delim=\\t"),
+        (',', "--delim=,"),
+    ] {
+        let path = env::temp_dir().join(format!(
+            "brc-delim-{}-{}.txt",
+            std::process::id(),
+            delim as u32
+        ));
+        fs::write(&path, input.replace(';', &delim.to_string())).unwrap();
+        let path = path.to_str().unwrap();
+
+        assert_eq!(run(&[flag, path]), golden, "{flag}");
+        assert_eq!(run(&[flag, "--thousands-sep", "_", path]), golden, "{flag}");
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn delimiter_must_not_be_part_of_a_value() {
+    for delim in ["--delim=.", "--delim=-", "--delim=7", "--delim=ab"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+            .args([delim, "measurements.txt"])
+            .output()
+            .expect("failed to run brc-rust");
+        assert!(!output.status.success(), "{delim}");
+    }
+}
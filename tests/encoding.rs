@@ -0,0 +1,44 @@
+use std::{path::Path, process::Command};
+
+fn fixture(name: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn run(args: &[&str]) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(
+        output.status.success(),
+        "brc-rust exited with {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    output.stdout
+}
+
+/// Latin-1 names are printed as UTF-8 under `--encoding latin1`, and as the
+/// bytes they are otherwise
+#[test]
+fn latin1_names_are_printed_as_utf8() {
+    let path = fixture("latin1.txt");
+
+    let output = run(&["--encoding", "latin1", &path]);
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "{São Paulo=2.0/2.0/2.0, Zürich=1.0/2.0/3.0}"
+    );
+    let output = run(&["--encoding", "latin1", "--format", "json", &path]);
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .contains(r#"{"station": "Zürich", "min": 1.0, "mean": 2.0, "max": 3.0, "count": 2}"#));
+
+    let output = run(&[&path]);
+    assert_eq!(output, b"{S\xe3o Paulo=2.0/2.0/2.0, Z\xfcrich=1.0/2.0/3.0}");
+}
@@ -0,0 +1,147 @@
+use std::{env, io, path::Path, process::Command};
+
+fn run(path: &str) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .arg(path)
+        .output()
+        .expect("failed to run brc-rust")
+}
+
+/// Asserts the command line fails with a message naming `path` rather than
+/// a panic.
+fn assert_reported(path: &str) {
+    let output = run(path);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert_eq!(output.status.code(), Some(1), "{stderr}");
+    assert!(stderr.starts_with(&format!("{path}: ")), "{stderr}");
+    assert!(!stderr.contains("panicked"), "{stderr}");
+}
+
+#[test]
+fn nonexistent_path_is_an_error() {
+    let path = env::temp_dir().join(format!("brc-missing-{}.txt", std::process::id()));
+    let path = path.to_str().unwrap();
+
+    let err = brc_rust::aggregate_file(path).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    assert_reported(path);
+}
+
+/// A directory opens fine but can't be mapped.
+#[test]
+fn unmappable_path_is_an_error() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let path = path.to_str().unwrap();
+
+    assert!(brc_rust::aggregate_file(path).is_err());
+    assert_reported(path);
+}
+
+#[cfg(unix)]
+#[test]
+fn unreadable_path_is_an_error() {
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    let path = env::temp_dir().join(format!("brc-unreadable-{}.txt", std::process::id()));
+    fs::write(&path, "Abha;1.0\n").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o000)).unwrap();
+
+    // Permissions don't stop root
+    if fs::read(&path).is_err() {
+        let path = path.to_str().unwrap();
+        let err = brc_rust::aggregate_file(path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        assert_reported(path);
+    }
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn invalid_utf8_name_is_an_error_under_strict() {
+    let path = env::temp_dir().join(format!("brc-invalid-utf8-{}.txt", std::process::id()));
+    std::fs::write(&path, b"Abha;1.0\nSa\xffo Paulo;2.0\nAbha;3.0\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .arg("--strict")
+        .arg(&path)
+        .output()
+        .expect("failed to run brc-rust");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "{stderr}");
+    assert!(output.stdout.is_empty());
+    assert!(
+        stderr.contains("invalid UTF-8 in station name at byte 2: Sa\u{fffd}o Paulo"),
+        "{stderr}"
+    );
+
+    // Valid names pass
+    std::fs::write(&path, "Abha;1.0\nSão Paulo;2.0\n").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .arg("--strict")
+        .arg(&path)
+        .output()
+        .expect("failed to run brc-rust");
+    std::fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "{Abha=1.0/1.0/1.0, São Paulo=2.0/2.0/2.0}"
+    );
+}
+
+#[cfg(not(feature = "gzip"))]
+#[test]
+fn gzipped_output_requires_the_gzip_feature() {
+    let input = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/measurements.txt");
+    let path = env::temp_dir().join(format!("brc-no-gzip-{}.out.gz", std::process::id()));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .arg("--output")
+        .arg(&path)
+        .arg(input)
+        .output()
+        .expect("failed to run brc-rust");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "{stderr}");
+    assert!(
+        stderr.contains("requires building with the `gzip` feature"),
+        "{stderr}"
+    );
+    assert!(!path.exists());
+}
+
+#[cfg(not(feature = "gzip"))]
+#[test]
+fn gzipped_input_requires_the_gzip_feature() {
+    let path = env::temp_dir().join(format!("brc-no-gunzip-{}.txt.gz", std::process::id()));
+    std::fs::write(&path, [0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff]).unwrap();
+    let path = path.to_str().unwrap();
+
+    let stderr = String::from_utf8_lossy(&run(path).stderr).into_owned();
+    assert_reported(path);
+    std::fs::remove_file(path).unwrap();
+    assert!(
+        stderr.contains("requires building with the `gzip` feature"),
+        "{stderr}"
+    );
+}
+
+#[cfg(not(feature = "sqlite"))]
+#[test]
+fn sqlite_format_requires_the_sqlite_feature() {
+    let input = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/measurements.txt");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(["--format", "sqlite"])
+        .arg(input)
+        .output()
+        .expect("failed to run brc-rust");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "{stderr}");
+    assert!(output.stdout.is_empty());
+    assert!(
+        stderr.contains("--format sqlite requires building with the `sqlite` feature"),
+        "{stderr}"
+    );
+}
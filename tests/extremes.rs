@@ -0,0 +1,64 @@
+use std::{path::Path, process::Command};
+
+fn fixture(name: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn run(args: &[&str]) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(
+        output.status.success(),
+        "brc-rust exited with {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    output.stdout
+}
+
+#[test]
+fn global_extremes_name_every_station_reaching_them() {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(["--global-extremes", &fixture("global_extremes.txt")])
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(output.status.success());
+
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "global min: -5.0 (A, C)\nglobal max: 9.0 (B, D)\n"
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "{A=-5.0/-1.5/2.0, B=-1.0/4.0/9.0, C=-5.0/-5.0/-5.0, D=9.0/9.0/9.0}"
+    );
+}
+
+/// Each extreme carries the timestamp it was first measured at, also when a
+/// later worker measures it again
+#[test]
+fn timestamps_of_the_extremes_are_reported() {
+    for threads in ["1", "3"] {
+        let output = run(&[
+            "--timestamp-col",
+            "--small-file-threshold",
+            "0",
+            "--threads",
+            threads,
+            &fixture("timestamps.txt"),
+        ]);
+        assert_eq!(
+            String::from_utf8_lossy(&output),
+            "{Abha=-3.0@2024-01-02/3.6/9.5@2024-01-03, \
+             Baku=1.0@2024-02-01/1.0/1.0@2024-02-01}",
+            "{threads} threads"
+        );
+    }
+}
@@ -0,0 +1,74 @@
+#![cfg(unix)]
+
+use std::{
+    ffi::c_int,
+    fs,
+    io::{self, Write},
+    os::{fd::AsRawFd, unix::process::CommandExt},
+    path::Path,
+    process::Command,
+};
+
+extern "C" {
+    fn fcntl(fd: c_int, cmd: c_int, ...) -> c_int;
+}
+
+#[test]
+fn pipes_passed_as_fds_are_merged() {
+    const F_SETFD: c_int = 2;
+
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let input = fs::read_to_string(fixtures.join("measurements.txt")).unwrap();
+    let golden = fs::read(fixtures.join("measurements.golden")).unwrap();
+
+    // Each half through its own pipe, small enough to fit in its buffer
+    let lines = input.lines().collect::<Vec<_>>();
+    let (first, second) = lines.split_at(lines.len() / 2);
+    let mut readers = Vec::new();
+    for half in [first, second] {
+        let (reader, mut writer) = io::pipe().unwrap();
+        writer
+            .write_all((half.join("\n") + "\n").as_bytes())
+            .unwrap();
+        readers.push(reader);
+    }
+
+    let fds = readers.iter().map(|r| r.as_raw_fd()).collect::<Vec<_>>();
+    let mut command = Command::new(env!("CARGO_BIN_EXE_brc-rust"));
+    for fd in &fds {
+        command.args(["--fd", &fd.to_string()]);
+    }
+    // Pipes are opened close-on-exec, the child has to inherit the readers
+    unsafe {
+        command.pre_exec(move || {
+            for &fd in &fds {
+                if fcntl(fd, F_SETFD, 0) == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
+    let output = command.output().expect("failed to run brc-rust");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&golden)
+    );
+}
+
+#[test]
+fn closed_fd_is_an_error() {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(["--fd", "999"])
+        .output()
+        .expect("failed to run brc-rust");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("fd 999: "));
+}
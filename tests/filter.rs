@@ -0,0 +1,115 @@
+use std::{path::Path, process::Command};
+
+fn fixture(name: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn run(args: &[&str]) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(
+        output.status.success(),
+        "brc-rust exited with {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    output.stdout
+}
+
+/// The stations `--filter` drops add up to `__other__`, so totals reconcile
+/// with the unfiltered output.
+#[test]
+fn other_bucket_holds_the_filtered_out_records() {
+    let path = fixture("measurements.txt");
+    let partials = |args: &[&str]| {
+        let output = run(&[&["--format", "partial"][..], args, &[&path]].concat());
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let fields = line.rsplitn(5, ';').collect::<Vec<_>>();
+                let tenths = fields[1].replace('.', "").parse::<i64>().unwrap();
+                (
+                    fields[4].to_string(),
+                    tenths,
+                    fields[0].parse::<u64>().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let all = partials(&[]);
+    let filtered = partials(&["--filter", "Abha,Accra", "--other-bucket"]);
+    let names = filtered
+        .iter()
+        .map(|(name, ..)| name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["Abha", "Accra", "__other__"]);
+
+    let total = |stations: &[(String, i64, u64)]| {
+        stations
+            .iter()
+            .fold((0, 0), |(sum, count), s| (sum + s.1, count + s.2))
+    };
+    let dropped = all
+        .iter()
+        .filter(|(name, ..)| name != "Abha" && name != "Accra")
+        .cloned()
+        .collect::<Vec<_>>();
+    let other = &filtered[2];
+    assert_eq!((other.1, other.2), total(&dropped));
+    assert_eq!(total(&filtered), total(&all));
+}
+
+/// Stations with fewer than 13 readings are dropped, the others are printed
+/// as they are without `--min-count`.
+#[test]
+fn min_count_drops_rare_stations() {
+    let path = fixture("measurements.txt");
+    let all = String::from_utf8(run(&["--format", "partial", &path])).unwrap();
+    let kept =
+        String::from_utf8(run(&["--format", "partial", "--min-count", "13", &path])).unwrap();
+
+    let frequent = all
+        .lines()
+        .filter(|line| line.rsplit(';').next().unwrap().parse::<u32>().unwrap() >= 13)
+        .collect::<Vec<_>>();
+    assert_eq!(frequent.len(), 9);
+    assert_eq!(kept.lines().collect::<Vec<_>>(), frequent);
+
+    let text = run(&["--min-count", "13", &path]);
+    assert_eq!(
+        String::from_utf8_lossy(&text),
+        "{Abha=-77.2/-9.7/99.9, Abidjan=-98.7/0.3/96.3, Cabo San Lucas=-53.7/25.2/89.2, \
+         Jos=-99.0/-6.9/99.9, Kyiv=-94.4/-21.2/98.1, Ouagadougou=-93.9/-13.1/86.6, \
+         Petropavlovsk-Kamchatsky=-88.8/-1.8/95.9, Zürich=-99.9/2.0/95.3, İzmir=-93.3/-4.3/87.4}"
+    );
+}
+
+#[test]
+fn seeded_stations_appear_without_values() {
+    let seeds = fixture("seed_stations.txt");
+    for threads in ["1", "3"] {
+        let output = run(&[
+            "--small-file-threshold",
+            "0",
+            "--threads",
+            threads,
+            "--seed-stations",
+            &seeds,
+            &fixture("three_lines.txt"),
+        ]);
+        assert_eq!(
+            String::from_utf8_lossy(&output),
+            "{Abha=1.0/2.0/3.0, Baku=-2.5/-2.5/-2.5, Nowhere=NA/NA/NA}",
+            "{threads} threads"
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use std::{env, fs, path::Path, process::Command};
+
+fn fixture(name: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn run(args: &[&str]) -> std::process::Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+#[test]
+fn every_station_is_looked_up_as_written() {
+    let path = env::temp_dir().join(format!("brc-flat-{}.bin", std::process::id()));
+    let flat = path.to_str().unwrap();
+    run(&[
+        "--format",
+        "flat",
+        "--output",
+        flat,
+        &fixture("measurements.txt"),
+    ]);
+
+    let golden = fs::read_to_string(fixture("measurements.golden")).unwrap();
+    let entries = golden
+        .trim_end()
+        .trim_start_matches('{')
+        .trim_end_matches('}');
+    for entry in entries.split(", ") {
+        let (name, _) = entry.split_once('=').unwrap();
+        let output = run(&["--lookup", name, flat]);
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            format!("{{{entry}}}")
+        );
+    }
+
+    let output = run(&["--lookup", "Nowhere", flat]);
+    fs::remove_file(&path).unwrap();
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "Nowhere not found\n"
+    );
+}
+
+/// A record count so large the records would end past the address space
+#[test]
+fn overflowing_record_count_is_not_found() {
+    let mut buf = b"BRCF".to_vec();
+    buf.extend(1u32.to_le_bytes());
+    buf.extend(u64::MAX.to_le_bytes());
+    buf.extend([0; 64]);
+
+    let path = env::temp_dir().join(format!("brc-flat-overflow-{}.bin", std::process::id()));
+    fs::write(&path, &buf).unwrap();
+    let output = run(&["--lookup", "Abha", path.to_str().unwrap()]);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "Abha not found\n");
+}
@@ -0,0 +1,246 @@
+use std::{fs, path::Path, process::Command};
+
+fn fixture(name: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn run(args: &[&str]) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(
+        output.status.success(),
+        "brc-rust exited with {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    output.stdout
+}
+
+#[test]
+fn output_matches_golden_file() {
+    let golden = fs::read(fixture("measurements.golden")).unwrap();
+
+    let output = run(&[&fixture("measurements.txt")]);
+
+    assert_eq!(
+        String::from_utf8_lossy(&output),
+        String::from_utf8_lossy(&golden)
+    );
+}
+
+#[test]
+fn threaded_output_matches_golden_file() {
+    let golden = fs::read(fixture("measurements.golden")).unwrap();
+
+    let output = run(&["--small-file-threshold", "0", &fixture("measurements.txt")]);
+
+    assert_eq!(
+        String::from_utf8_lossy(&output),
+        String::from_utf8_lossy(&golden)
+    );
+}
+
+#[test]
+fn more_threads_than_lines() {
+    let output = run(&[
+        "--small-file-threshold",
+        "0",
+        "--threads",
+        "16",
+        &fixture("three_lines.txt"),
+    ]);
+
+    assert_eq!(
+        String::from_utf8_lossy(&output),
+        "{Abha=1.0/2.0/3.0, Baku=-2.5/-2.5/-2.5}"
+    );
+}
+
+#[test]
+fn extremes_of_the_value_range() {
+    let output = run(&[&fixture("value_range.txt")]);
+
+    assert_eq!(
+        String::from_utf8_lossy(&output),
+        "{Max=-99.9/0.0/99.9, Min=-99.9/0.0/99.9, Zero=0.0/0.0/0.0}"
+    );
+}
+
+#[test]
+fn stations_with_a_single_small_reading() {
+    let expected = "{Negative=-0.1/-0.1/-0.1, Top=99.9/99.9/99.9, Zero=0.0/0.0/0.0}";
+    let output = run(&[&fixture("single_readings.txt")]);
+    assert_eq!(String::from_utf8_lossy(&output), expected);
+
+    let output = run(&["--reference-compat", &fixture("single_readings.txt")]);
+    assert_eq!(String::from_utf8_lossy(&output), format!("{expected}\n"));
+}
+
+#[test]
+fn empty_and_unterminated_files() {
+    let cases = [
+        ("empty.txt", "{}"),
+        ("single_newline.txt", "{}"),
+        (
+            "no_trailing_newline.txt",
+            "{Abha=1.5/1.5/1.5, Baku=-2.5/-2.5/-2.5}",
+        ),
+        ("blank_lines.txt", "{Abha=1.5/2.5/3.5, Baku=2.5/2.5/2.5}"),
+    ];
+    for (name, expected) in cases {
+        for threads in ["1", "2"] {
+            let output = run(&[
+                "--small-file-threshold",
+                "0",
+                "--threads",
+                threads,
+                &fixture(name),
+            ]);
+            assert_eq!(
+                String::from_utf8_lossy(&output),
+                expected,
+                "{name} with {threads} threads"
+            );
+        }
+    }
+}
+
+#[test]
+fn pinned_merge_output_matches_golden_file() {
+    let golden = fs::read(fixture("measurements.golden")).unwrap();
+
+    for merge in ["--streaming-merge", "--dump-partials"] {
+        let output = run(&[
+            "--small-file-threshold",
+            "0",
+            "--pin-merge",
+            "0",
+            merge,
+            &fixture("measurements.txt"),
+        ]);
+
+        assert_eq!(
+            String::from_utf8_lossy(&output),
+            String::from_utf8_lossy(&golden)
+        );
+    }
+}
+
+#[test]
+fn merged_stations_are_unique() {
+    let golden = fs::read(fixture("measurements.golden")).unwrap();
+
+    let output = run(&[
+        "--small-file-threshold",
+        "0",
+        "--threads",
+        "4",
+        "--verify-unique",
+        &fixture("measurements.txt"),
+    ]);
+
+    assert_eq!(
+        String::from_utf8_lossy(&output),
+        String::from_utf8_lossy(&golden)
+    );
+}
+
+#[test]
+fn workers_run_on_small_stacks() {
+    let golden = fs::read(fixture("measurements.golden")).unwrap();
+
+    for merge in ["--streaming-merge", "--dump-partials"] {
+        let output = run(&[
+            "--small-file-threshold",
+            "0",
+            "--threads",
+            "4",
+            "--thread-stack-size",
+            "65536",
+            merge,
+            &fixture("measurements.txt"),
+        ]);
+
+        assert_eq!(
+            String::from_utf8_lossy(&output),
+            String::from_utf8_lossy(&golden)
+        );
+    }
+}
+
+#[test]
+fn crlf_input_matches_golden_file() {
+    let golden = fs::read(fixture("measurements.golden")).unwrap();
+    let input = fs::read_to_string(fixture("measurements.txt")).unwrap();
+
+    let path = std::env::temp_dir().join(format!("brc-crlf-{}.txt", std::process::id()));
+    fs::write(&path, input.replace('\n', "\r\n")).unwrap();
+    let path = path.to_str().unwrap();
+
+    // Several workers, so chunks start right after a `\r\n` too
+    for threads in ["1", "4"] {
+        let output = run(&["--small-file-threshold", "0", "--threads", threads, path]);
+        assert_eq!(
+            String::from_utf8_lossy(&output),
+            String::from_utf8_lossy(&golden),
+            "{threads} threads"
+        );
+    }
+    fs::remove_file(path).unwrap();
+}
+
+/// Pins (or on macOS, tags) a worker to each available CPU. Whether the
+/// platform honours it or not, the workers carry on.
+#[test]
+fn workers_on_every_cpu_match_golden_file() {
+    let golden = fs::read(fixture("measurements.golden")).unwrap();
+    let cpus = std::thread::available_parallelism().unwrap().get();
+
+    let output = run(&[
+        "--small-file-threshold",
+        "0",
+        "--threads",
+        &cpus.to_string(),
+        &fixture("measurements.txt"),
+    ]);
+
+    assert_eq!(
+        String::from_utf8_lossy(&output),
+        String::from_utf8_lossy(&golden)
+    );
+}
+
+/// `half_tenths.reference` is the output of the Java reference
+/// implementation, which rounds the means half up whatever `--rounding` says
+#[test]
+fn reference_compat_matches_reference_output() {
+    let reference = fs::read(fixture("half_tenths.reference")).unwrap();
+    for rounding in ["nearest", "floor"] {
+        let output = run(&[
+            "--reference-compat",
+            "--rounding",
+            rounding,
+            &fixture("half_tenths.txt"),
+        ]);
+        assert_eq!(
+            String::from_utf8_lossy(&output),
+            String::from_utf8_lossy(&reference),
+            "--rounding {rounding}"
+        );
+    }
+
+    let mut golden = fs::read(fixture("measurements.golden")).unwrap();
+    golden.push(b'\n');
+    let output = run(&["--reference-compat", &fixture("measurements.txt")]);
+    assert_eq!(
+        String::from_utf8_lossy(&output),
+        String::from_utf8_lossy(&golden)
+    );
+}
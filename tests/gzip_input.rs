@@ -0,0 +1,115 @@
+// Compressed with the system's `gzip`
+#![cfg(all(unix, feature = "gzip"))]
+
+use std::{
+    env, fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+};
+
+fn fixture() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/measurements.txt")
+}
+
+fn run(args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run brc-rust");
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn gzip(data: &[u8], level: &str) -> Vec<u8> {
+    let mut child = Command::new("gzip")
+        .args(["-c", level])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to run gzip");
+    child.stdin.take().unwrap().write_all(data).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    output.stdout
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |mut crc, &byte| {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                0xedb88320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+        crc
+    })
+}
+
+#[test]
+fn gzipped_input_matches_plain_input() {
+    let input = fs::read(fixture()).unwrap();
+    let plain = run(&[fixture().to_str().unwrap()], &[]);
+    assert!(plain.status.success());
+
+    let (first, second) = input.split_at(input.len() / 2);
+    let concatenated = [gzip(first, "-6"), gzip(second, "-6")].concat();
+    // A single stored block, as gzip writes incompressible data
+    let stored = [
+        &[0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff, 1][..],
+        &(input.len() as u16).to_le_bytes(),
+        &(!(input.len() as u16)).to_le_bytes(),
+        &input,
+        &crc32(&input).to_le_bytes(),
+        &(input.len() as u32).to_le_bytes(),
+    ]
+    .concat();
+
+    let dir = env::temp_dir();
+    let id = std::process::id();
+    for (name, gzipped) in [
+        ("fast", gzip(&input, "-1")),
+        ("best", gzip(&input, "-9")),
+        ("concatenated", concatenated),
+        ("stored", stored),
+    ] {
+        let path = dir.join(format!("brc-gzip-input-{id}-{name}.txt.gz"));
+        fs::write(&path, &gzipped).unwrap();
+        let from_file = run(&[path.to_str().unwrap()], &[]);
+        fs::remove_file(&path).unwrap();
+        let from_stdin = run(&["-"], &gzipped);
+
+        for output in [from_file, from_stdin] {
+            assert!(
+                output.status.success(),
+                "{name}: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            assert_eq!(output.stdout, plain.stdout, "{name}");
+        }
+    }
+}
+
+#[test]
+fn corrupt_gzipped_input_is_an_error() {
+    let mut gzipped = gzip(&fs::read(fixture()).unwrap(), "-6");
+    let crc = gzipped.len() - 8;
+    gzipped[crc] ^= 1;
+
+    let output = run(&["-"], &gzipped);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("checksum"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let output = run(&["-"], &gzipped[..gzipped.len() / 2]);
+    assert!(!output.status.success());
+}
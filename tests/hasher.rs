@@ -0,0 +1,235 @@
+use std::{collections::HashSet, fs, path::Path, process::Command};
+
+use brc_rust::{
+    hasher::{Djb2, Fx, StationHasher},
+    MAP_SIZE,
+};
+
+fn fixture(name: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn run(args: &[&str]) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(
+        output.status.success(),
+        "brc-rust exited with {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    output.stdout
+}
+
+fn stations() -> Vec<String> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/stations.txt");
+    fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Names whose home slot is already taken by an earlier one.
+fn collisions<H: StationHasher>(names: &[String]) -> usize {
+    let mut homes = HashSet::new();
+    names
+        .iter()
+        .filter(|name| !homes.insert(H::hash(name.as_bytes()) as usize % MAP_SIZE))
+        .count()
+}
+
+#[test]
+fn collisions_of_each_hasher() {
+    let names = stations();
+    assert_eq!(names.len(), 413);
+    // Both hashes are fixed, so are their collisions in the `MAP_SIZE` slots
+    assert_eq!(collisions::<Djb2>(&names), 0);
+    assert_eq!(collisions::<Fx>(&names), 12);
+
+    // The default hasher is the one the stations are aggregated with
+    let input = names
+        .iter()
+        .map(|name| format!("{name};1.0\n"))
+        .collect::<String>();
+    let path = std::env::temp_dir().join(format!("brc-hasher-{}.txt", std::process::id()));
+    fs::write(&path, input).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .arg("--hash-stats")
+        .arg(&path)
+        .output()
+        .expect("failed to run brc-rust");
+    fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+    let stats = String::from_utf8(output.stderr).unwrap();
+    assert!(stats.contains("collisions=0 "), "{stats}");
+}
+
+#[test]
+fn scanning_hashes_the_name_before_the_delimiter() {
+    for name in stations() {
+        let line = format!("{name};12.3\n");
+        let expected = (name.len(), Djb2::hash(name.as_bytes()));
+        assert_eq!(Djb2::scan(line.as_bytes(), 0, b';'), expected, "{name}");
+        assert_eq!(
+            Fx::scan(line.as_bytes(), 0, b';'),
+            (name.len(), Fx::hash(name.as_bytes())),
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn hash_stats_of_colliding_stations() {
+    // Three names sharing home slot 0, then one whose home slot 1 the second
+    // of them was probed into
+    let names = ["KMC", "MNK", "OOS", "KMD"];
+    let homes = names.map(|name| Djb2::hash(name.as_bytes()) as usize % MAP_SIZE);
+    assert_eq!(homes, [0, 0, 0, 1]);
+
+    let input = names
+        .iter()
+        .chain(&names)
+        .map(|name| format!("{name};1.0\n"))
+        .collect::<String>();
+    let path = std::env::temp_dir().join(format!("brc-hash-stats-{}.txt", std::process::id()));
+    fs::write(&path, input).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .arg("--hash-stats")
+        .arg(&path)
+        .output()
+        .expect("failed to run brc-rust");
+    fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "distinct=4 collisions=3 max_probe=2 load_factor=0.001\n"
+    );
+}
+
+#[test]
+fn shown_slots_follow_the_hash() {
+    const MAP_SIZE: u32 = 7599;
+    let hash = |name: &str| {
+        name.bytes().fold(5381u32, |hash, ch| {
+            (ch as u32)
+                .wrapping_add(hash << 6)
+                .wrapping_add(hash << 16)
+                .wrapping_sub(hash)
+        })
+    };
+
+    let output = run(&["--show-slots", &fixture("measurements.txt")]);
+    let output = String::from_utf8_lossy(&output);
+
+    let entries = output.trim_matches(['{', '}']).split("), ");
+    let mut count = 0;
+    for entry in entries {
+        let (name, rest) = entry.split_once('=').unwrap();
+        let (slot, probe) = rest
+            .split_once(" (slot ")
+            .unwrap()
+            .1
+            .trim_end_matches(')')
+            .split_once(", probe ")
+            .unwrap();
+        let (slot, probe) = (slot.parse::<u32>().unwrap(), probe.parse::<u32>().unwrap());
+
+        assert_eq!(slot, (hash(name) % MAP_SIZE + probe) % MAP_SIZE, "{name}");
+        count += 1;
+    }
+    assert_eq!(count, 16);
+}
+
+#[test]
+fn colliding_names_keep_separate_aggregates() {
+    // "Ayj" and "Baa" share the home slot 53
+    let path = fixture("colliding.txt");
+    for threads in ["1", "3"] {
+        let output = run(&["--small-file-threshold", "0", "--threads", threads, &path]);
+        assert_eq!(
+            String::from_utf8_lossy(&output),
+            "{Ayj=1.0/2.0/3.0, Baa=-5.0/-4.0/-3.0}",
+            "{threads} threads"
+        );
+    }
+
+    let output = run(&["--show-slots", &path]);
+    assert_eq!(
+        String::from_utf8_lossy(&output),
+        "{Ayj=1.0/2.0/3.0 (slot 53, probe 0), Baa=-5.0/-4.0/-3.0 (slot 54, probe 1)}"
+    );
+}
+
+#[test]
+fn merging_colliding_names_keeps_them_apart() {
+    // The workers see "Ayj" and "Baa", which share a home slot, in opposite
+    // orders, so each takes the other's slot in one of the worker tables
+    let path = fixture("colliding_merge.txt");
+    for merge in [&[][..], &["--streaming-merge"]] {
+        let args = ["--small-file-threshold", "0", "--threads", "2", &path];
+        let output = run(&[merge, &args].concat());
+        assert_eq!(
+            String::from_utf8_lossy(&output),
+            "{Ayj=-2.0/2.0/6.0, Baa=-1.0/2.5/5.0}",
+            "{merge:?}"
+        );
+    }
+}
+
+/// Names hashed a word at a time by `parse_line` land in the same slots as
+/// when hashed byte by byte, as the loose parser `--thousands-sep` selects
+/// does.
+#[test]
+fn word_hashes_match_byte_hashes() {
+    let names = (1..40)
+        .flat_map(|len| {
+            let ascii = (0..len).map(|i| (b'a' + (i * 7 % 26) as u8) as char);
+            let accented = ascii.clone().chain(['é']);
+            let mixed = ['ü'].into_iter().chain(ascii.clone());
+            [
+                ascii.collect::<String>(),
+                accented.collect(),
+                mixed.collect(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let input = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| format!("{name};{}.{}\n", i % 40, i % 10))
+        .collect::<String>();
+
+    let path = std::env::temp_dir().join(format!("brc-word-hash-{}.txt", std::process::id()));
+    fs::write(&path, input).unwrap();
+    let path = path.to_str().unwrap();
+
+    let words = run(&["--show-slots", path]);
+    let bytes = run(&["--show-slots", "--thousands-sep", "_", path]);
+    fs::remove_file(path).unwrap();
+    let words = String::from_utf8(words).unwrap();
+    assert_eq!(words.matches("(slot").count(), names.len(), "{words}");
+    assert_eq!(words, String::from_utf8(bytes).unwrap());
+}
+
+#[test]
+fn tune_recommends_a_listed_size() {
+    let output = run(&["--tune", &fixture("measurements.txt")]);
+    let output = String::from_utf8_lossy(&output);
+
+    let recommended = output
+        .lines()
+        .find_map(|line| line.strip_prefix("recommended MAP_SIZE: "))
+        .unwrap()
+        .parse::<usize>()
+        .unwrap();
+    assert!(recommended >= 16);
+    assert!(output.contains(&format!("size={recommended} distinct=16 ")));
+}
@@ -0,0 +1,34 @@
+use std::{env, fs, path::Path, process::Command};
+
+#[test]
+fn index_offsets_point_at_station_lines() {
+    let input_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/measurements.txt");
+    let input = fs::read(&input_path).unwrap();
+    let index_path = env::temp_dir().join(format!("brc-index-{}.txt", std::process::id()));
+
+    for threshold in ["1048576", "0"] {
+        let status = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+            .args(["--small-file-threshold", threshold, "--index"])
+            .arg(&index_path)
+            .arg(&input_path)
+            .output()
+            .expect("failed to run brc-rust")
+            .status;
+        assert!(status.success());
+
+        let index = fs::read_to_string(&index_path).unwrap();
+        let mut lines = 0;
+        for entry in index.lines() {
+            let (station, offsets) = entry.split_once(';').unwrap();
+            for offset in offsets.split(',') {
+                let offset = offset.parse::<usize>().unwrap();
+                assert!(offset == 0 || input[offset - 1] == b'\n');
+                assert!(input[offset..].starts_with(format!("{station};").as_bytes()));
+                lines += 1;
+            }
+        }
+        assert_eq!(lines, input.iter().filter(|&&ch| ch == b'\n').count());
+    }
+
+    fs::remove_file(&index_path).unwrap();
+}
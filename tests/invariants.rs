@@ -0,0 +1,43 @@
+use std::{env, fs, path::Path, process::Command};
+
+fn run(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .output()
+        .expect("failed to run brc-rust")
+}
+
+#[test]
+fn valid_aggregates_pass() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let golden = fs::read_to_string(fixtures.join("measurements.golden")).unwrap();
+
+    let input = fixtures.join("measurements.txt");
+    let output = run(&["--verify-invariants", input.to_str().unwrap()]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), golden);
+}
+
+/// Partial results are merged as they are, so corrupted ones reach the checks.
+#[test]
+fn corrupted_aggregates_are_flagged() {
+    let cases = [
+        ("swapped", "Abha;5.0;-5.0;0.0;2\n", "min 50 above max -50"),
+        ("empty", "Abha;1.0;1.0;1.0;0\n", "no values"),
+        ("sum", "Abha;-1.0;1.0;5.0;2\n", "sum 50 outside"),
+    ];
+
+    for (name, partial, err) in cases {
+        let path =
+            env::temp_dir().join(format!("brc-invariants-{}-{name}.txt", std::process::id()));
+        fs::write(&path, partial).unwrap();
+        let path = path.to_str().unwrap();
+
+        assert!(run(&["--merge-partials", path]).status.success(), "{name}");
+        let output = run(&["--verify-invariants", "--merge-partials", path]);
+        fs::remove_file(path).unwrap();
+        assert!(!output.status.success(), "{name}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(&format!("Abha: {err}")), "{name}: {stderr}");
+    }
+}
@@ -0,0 +1,195 @@
+use std::{env, fs, iter::Peekable, process::Command, str::Chars};
+
+#[derive(Debug, PartialEq)]
+enum Json {
+    Null,
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+/// A strict parser of the JSON subset the sink writes, failing on anything
+/// else.
+fn parse(text: &str) -> Json {
+    let mut chars = text.chars().peekable();
+    let value = parse_value(&mut chars);
+    skip_whitespace(&mut chars);
+    assert_eq!(chars.next(), None, "trailing characters");
+    value
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars
+        .next_if(|ch| matches!(ch, ' ' | '\t' | '\n' | '\r'))
+        .is_some()
+    {}
+}
+
+fn expect(chars: &mut Peekable<Chars>, expected: char) {
+    skip_whitespace(chars);
+    assert_eq!(chars.next(), Some(expected));
+}
+
+fn parse_value(chars: &mut Peekable<Chars>) -> Json {
+    skip_whitespace(chars);
+    match chars.peek().copied() {
+        Some('[') => {
+            chars.next();
+            let mut items = Vec::new();
+            skip_whitespace(chars);
+            if chars.next_if_eq(&']').is_none() {
+                loop {
+                    items.push(parse_value(chars));
+                    skip_whitespace(chars);
+                    match chars.next() {
+                        Some(',') => continue,
+                        Some(']') => break,
+                        other => panic!("unexpected {other:?} in array"),
+                    }
+                }
+            }
+            Json::Array(items)
+        }
+        Some('{') => {
+            chars.next();
+            let mut fields = Vec::new();
+            loop {
+                skip_whitespace(chars);
+                let Json::String(key) = parse_value(chars) else {
+                    panic!("non-string key");
+                };
+                expect(chars, ':');
+                fields.push((key, parse_value(chars)));
+                skip_whitespace(chars);
+                match chars.next() {
+                    Some(',') => continue,
+                    Some('}') => break,
+                    other => panic!("unexpected {other:?} in object"),
+                }
+            }
+            Json::Object(fields)
+        }
+        Some('"') => {
+            chars.next();
+            let mut value = String::new();
+            loop {
+                match chars.next().expect("unterminated string") {
+                    '"' => break,
+                    '\\' => match chars.next().unwrap() {
+                        '"' => value.push('"'),
+                        '\\' => value.push('\\'),
+                        'n' => value.push('\n'),
+                        'r' => value.push('\r'),
+                        't' => value.push('\t'),
+                        'u' => {
+                            let code = (0..4).map(|_| chars.next().unwrap()).collect::<String>();
+                            value.push(
+                                char::from_u32(u32::from_str_radix(&code, 16).unwrap()).unwrap(),
+                            );
+                        }
+                        other => panic!("invalid escape \\{other}"),
+                    },
+                    ch => {
+                        assert!(ch >= ' ', "unescaped control character");
+                        value.push(ch);
+                    }
+                }
+            }
+            Json::String(value)
+        }
+        Some('n') => {
+            let word = (0..4).map(|_| chars.next().unwrap()).collect::<String>();
+            assert_eq!(word, "null");
+            Json::Null
+        }
+        _ => {
+            let mut number = String::new();
+            while let Some(ch) = chars.next_if(|ch| ch.is_ascii_digit() || matches!(ch, '-' | '.'))
+            {
+                number.push(ch);
+            }
+            Json::Number(
+                number
+                    .parse()
+                    .unwrap_or_else(|_| panic!("invalid number {number:?}")),
+            )
+        }
+    }
+}
+
+fn run(name: &str, input: impl AsRef<[u8]>, args: &[&str]) -> String {
+    let path = env::temp_dir().join(format!("brc-json-{}-{name}.txt", std::process::id()));
+    fs::write(&path, input).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .arg(&path)
+        .output()
+        .expect("failed to run brc-rust");
+    fs::remove_file(&path).unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn station(name: &str, min: f64, mean: f64, max: f64, count: f64) -> Json {
+    Json::Object(vec![
+        ("station".to_string(), Json::String(name.to_string())),
+        ("min".to_string(), Json::Number(min)),
+        ("mean".to_string(), Json::Number(mean)),
+        ("max".to_string(), Json::Number(max)),
+        ("count".to_string(), Json::Number(count)),
+    ])
+}
+
+#[test]
+fn output_is_valid_json() {
+    let input = "Zürich;3.5\nSay \"hi\";-1.0\nBack\\slash;2.0\nTab\there;1.5\nZürich;-0.5\n";
+    let output = run("valid", input, &["--format=json"]);
+
+    assert_eq!(
+        parse(&output),
+        Json::Array(vec![
+            station("Back\\slash", 2.0, 2.0, 2.0, 1.),
+            station("Say \"hi\"", -1.0, -1.0, -1.0, 1.),
+            station("Tab\there", 1.5, 1.5, 1.5, 1.),
+            station("Zürich", -0.5, 1.5, 3.5, 2.),
+        ]),
+        "{output}"
+    );
+    // One decimal like the text format
+    assert!(output.contains("\"max\": 2.0,"), "{output}");
+}
+
+#[test]
+fn empty_input_is_an_empty_array() {
+    assert_eq!(
+        parse(&run("empty", "", &["--format", "json"])),
+        Json::Array(vec![])
+    );
+}
+
+#[test]
+fn default_format_is_text() {
+    let input = "Abha;1.0\n";
+    assert_eq!(
+        run("default", input, &["--format=default"]),
+        run("text", input, &[])
+    );
+}
+
+/// Names that aren't valid UTF-8 still make valid JSON, with replacement
+/// characters
+#[test]
+fn invalid_utf8_names_are_replaced() {
+    let output = run("invalid", b"x\xffy;1.0\n", &["--format", "json"]);
+
+    assert_eq!(
+        parse(&output),
+        Json::Array(vec![station("x\u{fffd}y", 1.0, 1.0, 1.0, 1.)])
+    );
+}
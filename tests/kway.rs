@@ -0,0 +1,58 @@
+use std::{env, fs, path::Path, process::Command};
+
+fn run(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(
+        output.status.success(),
+        "brc-rust exited with {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Many stations over several chunks, so each worker sees most of them.
+#[test]
+fn kway_merge_matches_sorted_table() {
+    let dir = env::temp_dir();
+    let mut paths = Vec::new();
+    for file in 0..2 {
+        let path = dir.join(format!("brc-kway-{}-{file}.txt", std::process::id()));
+        let input = (0..200_000u32)
+            .map(|i| {
+                let station = (i * 7919 + file) % 5_000;
+                format!("Station {station};{}.{}\n", (i % 90) as i32 - 40, i % 10)
+            })
+            .collect::<String>();
+        fs::write(&path, input).unwrap();
+        paths.push(path.to_str().unwrap().to_owned());
+    }
+
+    let mut args = vec!["--small-file-threshold", "0", "--threads", "3"];
+    for path in &paths {
+        args.push(path);
+        let expected = run(&args);
+        assert!(expected.matches('=').count() > 4_000, "{expected}");
+
+        let mut kway_args = args.clone();
+        kway_args.push("--kway-merge");
+        assert_eq!(run(&kway_args), expected, "{args:?}");
+    }
+    for path in paths {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn kway_merge_matches_golden() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let golden = fs::read_to_string(fixtures.join("measurements.golden")).unwrap();
+
+    let input = fixtures.join("measurements.txt");
+    let output = run(&["--kway-merge", input.to_str().unwrap()]);
+    assert_eq!(output, golden);
+}
@@ -0,0 +1,47 @@
+use std::thread;
+
+use brc_rust::{
+    hasher::{Djb2, StationHasher},
+    LiveTable,
+};
+
+const VALUES: u32 = 200_000;
+
+/// Snapshots taken while another thread records hold whole values only: the
+/// count never shrinks, the mean lies between the extremes and the last one
+/// holds every value.
+#[test]
+fn snapshots_while_filling_are_consistent() {
+    let live = LiveTable::new();
+    let name: &'static [u8] = b"Abha";
+    assert_eq!(live.snapshot(name), None);
+
+    let snapshots = thread::scope(|s| {
+        let writer = s.spawn(|| {
+            for i in 0..VALUES {
+                live.record(name, Djb2::hash(name), (i % 201) as i32 - 100);
+            }
+        });
+
+        let mut snapshots = Vec::new();
+        while !writer.is_finished() {
+            snapshots.extend(live.snapshot(name));
+        }
+        snapshots
+    });
+
+    for pair in snapshots.windows(2) {
+        assert!(pair[0].count <= pair[1].count, "{pair:?}");
+    }
+    for stats in &snapshots {
+        assert!(
+            stats.min <= stats.mean && stats.mean <= stats.max,
+            "{stats:?}"
+        );
+    }
+
+    let last = live.snapshot(name).unwrap();
+    assert_eq!(last.count, VALUES as u64);
+    assert_eq!((last.min, last.max), (-10.0, 10.0));
+    assert_eq!(live.snapshot(b"Baku"), None);
+}
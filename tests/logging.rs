@@ -0,0 +1,52 @@
+#![cfg(feature = "logging")]
+
+use std::{env, fs, path::Path, sync::Mutex};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// Keeps the warnings logged while the tests run.
+struct Capture(Mutex<Vec<String>>);
+
+impl Log for Capture {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Warn
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            self.0.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: Capture = Capture(Mutex::new(Vec::new()));
+
+#[test]
+fn failed_merge_pinning_is_logged() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(LevelFilter::Warn);
+
+    let input = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/measurements.txt");
+    let output = env::temp_dir().join(format!("brc-logging-{}.txt", std::process::id()));
+    let args = [
+        "--small-file-threshold",
+        "0",
+        "--threads",
+        "2",
+        "--pin-merge",
+        "100000",
+        "--output",
+        output.to_str().unwrap(),
+        input.to_str().unwrap(),
+    ];
+    brc_rust::run_with_args(args.map(String::from)).unwrap();
+    fs::remove_file(&output).unwrap();
+
+    let warnings = LOGGER.0.lock().unwrap();
+    assert!(
+        warnings.contains(&"failed to pin the merge thread to CPU 100000".to_string()),
+        "{warnings:?}"
+    );
+}
@@ -0,0 +1,63 @@
+use std::{env, fs, process::Command};
+
+const STATIONS: u32 = 10_000;
+
+fn run(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(["--small-file-threshold", "0", "--threads", "7"])
+        .args(args)
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn input() -> String {
+    (0..STATIONS * 5)
+        .map(|i| {
+            format!(
+                "Station {};{}.{}\n",
+                i * 7 % STATIONS,
+                i as i32 % 97 - 48,
+                i % 10
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn pairwise_merge_matches_serial_merge() {
+    let path = env::temp_dir().join(format!("brc-merge-{}.txt", std::process::id()));
+    fs::write(&path, input()).unwrap();
+    let path = path.to_str().unwrap();
+
+    // The same worker tables, merged pairwise by default and one after the
+    // other on the thread pinned by `--pin-merge`
+    for inputs in [&[path][..], &[path, path]] {
+        let pairwise = run(inputs);
+        let serial = run(&[&["--pin-merge", "0"], inputs].concat());
+        assert_eq!(pairwise.matches('=').count(), STATIONS as usize);
+        assert_eq!(pairwise, serial, "{} inputs", inputs.len());
+    }
+    fs::remove_file(path).unwrap();
+}
+
+/// Tables sent over the channel as each worker finishes add up to the same
+/// aggregates as the tables merged once all workers are done.
+#[test]
+fn streamed_merge_matches_in_place_merge() {
+    let path = env::temp_dir().join(format!("brc-merge-streamed-{}.txt", std::process::id()));
+    fs::write(&path, input()).unwrap();
+    let path = path.to_str().unwrap();
+
+    let in_place = run(&["--format", "partial", path]);
+    let streamed = run(&["--format", "partial", "--streaming-merge", path]);
+    fs::remove_file(path).unwrap();
+    assert_eq!(in_place.lines().count(), STATIONS as usize);
+    assert_eq!(streamed, in_place);
+}
@@ -0,0 +1,74 @@
+use std::{env, fs, path::PathBuf};
+
+fn map(name: &str, contents: &str) -> (PathBuf, &'static [u8]) {
+    let path = env::temp_dir().join(format!("brc-mmap-{}-{name}.txt", std::process::id()));
+    fs::write(&path, contents).unwrap();
+    let mapped = brc_rust::map_file(&fs::File::open(&path).unwrap()).unwrap();
+
+    (path, mapped)
+}
+
+#[test]
+fn mapped_file_equals_its_contents() {
+    let contents = (0..10_000)
+        .map(|i| format!("Station {};{}.{}\n", i % 97, i % 50, i % 10))
+        .collect::<String>();
+
+    let (path, mapped) = map("full", &contents);
+    assert_eq!(mapped, contents.as_bytes());
+    let (empty_path, empty) = map("empty", "");
+    assert!(empty.is_empty());
+
+    // Windows refuses to remove files that are still mapped
+    for mapped in [mapped, empty] {
+        // SAFETY: the mappings aren't used past this point
+        unsafe { brc_rust::unmap(mapped) };
+    }
+    for path in [path, empty_path] {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn repeated_aggregation_releases_mappings() {
+    let path = env::temp_dir().join(format!("brc-mmap-{}-repeated.txt", std::process::id()));
+    fs::write(&path, "Abha;1.0\nHamburg;-2.5\n").unwrap();
+
+    for _ in 0..50 {
+        let stations = brc_rust::aggregate_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(stations.len(), 2);
+    }
+
+    let maps = fs::read_to_string("/proc/self/maps").unwrap();
+    let path = path.to_str().unwrap();
+    let mapped = maps.lines().filter(|line| line.ends_with(path)).count();
+    fs::remove_file(path).unwrap();
+    assert_eq!(mapped, 0, "{maps}");
+}
+
+/// The chunks are sized by the mapping, which keeps the length the file had
+/// when it was mapped even if the file grows afterwards.
+#[cfg(unix)]
+#[test]
+fn chunks_add_up_to_the_mapped_length() {
+    let contents = (0..1000)
+        .map(|i| format!("Station {};{}.{}\n", i % 13, i % 50, i % 10))
+        .collect::<String>();
+    let (path, mapped) = map("chunks", &contents);
+
+    let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+    std::io::Write::write_all(&mut file, b"Late;1.0\n").unwrap();
+    assert_eq!(mapped.len(), contents.len());
+
+    for threads in [1, 3, 8] {
+        let bounds = brc_rust::chunk_bounds(mapped, threads);
+        let sizes = bounds.iter().map(|(start, end)| end - start).sum::<usize>();
+        assert_eq!(sizes, mapped.len(), "{threads} threads: {bounds:?}");
+        assert_eq!(bounds.last().unwrap().1, mapped.len(), "{threads} threads");
+    }
+
+    // SAFETY: the mapping isn't used past this point
+    unsafe { brc_rust::unmap(mapped) };
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,44 @@
+#![cfg(feature = "memmap2")]
+
+use std::{env, fs, path::Path};
+
+use brc_rust::{map_file_memmap2, map_file_native, unmap};
+
+#[test]
+fn both_mmap_backends_map_identical_bytes() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let large = (0..300_000)
+        .map(|i| format!("Station{};{}.{}\n", i % 97, i % 100 - 50, i % 10))
+        .collect::<String>();
+    let large_path = env::temp_dir().join(format!("brc-mmap-backends-{}.txt", std::process::id()));
+    fs::write(&large_path, large).unwrap();
+
+    let paths = [
+        fixtures.join("measurements.txt"),
+        fixtures.join("empty.txt"),
+        fixtures.join("no_trailing_newline.txt"),
+        large_path.clone(),
+    ];
+    for path in &paths {
+        let file = fs::File::open(path).unwrap();
+        let memmap2 = map_file_memmap2(&file).unwrap();
+        let native = map_file_native(&file).unwrap();
+
+        assert!(memmap2 == fs::read(path).unwrap(), "{path:?}");
+        assert!(native == memmap2, "{path:?}");
+        for threads in [1, 4] {
+            assert_eq!(
+                brc_rust::aggregate(native, threads),
+                brc_rust::aggregate(memmap2, threads),
+                "{path:?}, {threads} threads"
+            );
+        }
+
+        // SAFETY: the mappings aren't used past this point
+        unsafe {
+            unmap(memmap2);
+            unmap(native);
+        }
+    }
+    fs::remove_file(large_path).unwrap();
+}
@@ -0,0 +1,151 @@
+use std::{env, fs, path::Path, process::Command};
+
+fn run(args: &[&str]) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(
+        output.status.success(),
+        "brc-rust exited with {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    output.stdout
+}
+
+/// Files spanning several chunks and files far smaller than one, sharing the
+/// same workers, aggregate like their concatenation.
+#[test]
+fn mixed_file_sizes_match_concatenation() {
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/measurements.txt");
+    let small = fs::read_to_string(fixture).unwrap();
+    let large = (0..300_000)
+        .map(|i| format!("Station{};{}.{}\n", i % 97, i % 100 - 50, i % 10))
+        .collect::<String>();
+    let tiny = "Station3;99.9\n".to_string();
+
+    let dir = env::temp_dir();
+    let id = std::process::id();
+    let mut paths = Vec::new();
+    for (name, contents) in [("large", &large), ("small", &small), ("tiny", &tiny)] {
+        let path = dir.join(format!("brc-multi-{id}-{name}.txt"));
+        fs::write(&path, contents).unwrap();
+        paths.push(path.to_string_lossy().into_owned());
+    }
+    let concatenated = dir.join(format!("brc-multi-{id}-all.txt"));
+    fs::write(&concatenated, large + &small + &tiny).unwrap();
+
+    let mut args = vec!["--small-file-threshold", "0", "--threads", "4"];
+    args.extend(paths.iter().map(String::as_str));
+    let output = run(&args);
+    let expected = run(&[concatenated.to_str().unwrap()]);
+
+    for path in paths {
+        fs::remove_file(path).unwrap();
+    }
+    fs::remove_file(concatenated).unwrap();
+
+    assert_eq!(
+        String::from_utf8_lossy(&output),
+        String::from_utf8_lossy(&expected)
+    );
+}
+
+/// A dataset split into two files, with and without workers merging their
+/// tables, aggregates like the whole of it.
+#[test]
+fn split_dataset_matches_whole() {
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/measurements.txt");
+    let whole = fs::read_to_string(&fixture).unwrap();
+    let lines = whole.lines().collect::<Vec<_>>();
+    let (first, second) = lines.split_at(lines.len() / 2);
+
+    let dir = env::temp_dir();
+    let id = std::process::id();
+    let mut paths = Vec::new();
+    for (name, half) in [("first", first), ("second", second)] {
+        let path = dir.join(format!("brc-split-{id}-{name}.txt"));
+        fs::write(&path, half.join("\n") + "\n").unwrap();
+        paths.push(path.to_string_lossy().into_owned());
+    }
+
+    let expected = run(&[fixture.to_str().unwrap()]);
+    for args in [&[][..], &["--small-file-threshold", "0", "--threads", "2"]] {
+        let mut args = args.to_vec();
+        args.extend(paths.iter().map(String::as_str));
+        assert_eq!(
+            String::from_utf8_lossy(&run(&args)),
+            String::from_utf8_lossy(&expected),
+            "{args:?}"
+        );
+    }
+
+    for path in paths {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+/// Per-worker reporting and streaming are of a single file's split, with two
+/// files they're refused rather than silently ignored.
+#[test]
+fn per_worker_flags_are_rejected_with_two_inputs() {
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/measurements.txt");
+    let fixture = fixture.to_str().unwrap();
+
+    for flag in ["--dump-partials", "--streaming-merge"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+            .args([flag, fixture, fixture])
+            .output()
+            .expect("failed to run brc-rust");
+        assert!(!output.status.success(), "{flag}");
+        assert!(output.stdout.is_empty(), "{flag}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("take a single input"), "{flag}: {stderr}");
+
+        // A single file still takes them
+        run(&[flag, fixture]);
+    }
+}
+
+#[test]
+fn missing_file_is_skipped_with_continue_on_error() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let missing = env::temp_dir().join(format!("brc-missing-input-{}.txt", std::process::id()));
+    let paths = [
+        fixtures.join("three_lines.txt"),
+        missing.clone(),
+        fixtures.join("half_tenths.txt"),
+    ];
+    let missing = missing.to_str().unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .arg("--continue-on-error")
+        .args(&paths)
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "{Abha=1.0/2.0/3.0, Baku=-2.5/-2.5/-2.5, D=2.2/2.3/2.3, E=-2.3/-2.2/-2.2, F=0.1/0.2/0.2}"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.starts_with(&format!("skipping {missing}: ")),
+        "{stderr}"
+    );
+    assert!(
+        stderr.ends_with(&format!("failed to process 1 file(s): {missing}\n")),
+        "{stderr}"
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(&paths)
+        .output()
+        .expect("failed to run brc-rust");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with(&format!("{missing}: ")), "{stderr}");
+}
@@ -0,0 +1,53 @@
+// Read back with the system's `gzip`
+#![cfg(all(unix, feature = "gzip"))]
+
+use std::{env, fs, process::Command};
+
+fn run(args: &[&str]) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(
+        output.status.success(),
+        "brc-rust exited with {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    output.stdout
+}
+
+/// Enough stations for the output to span several deflate blocks.
+#[test]
+fn gzipped_output_matches_plain_output() {
+    let dir = env::temp_dir();
+    let id = std::process::id();
+    let input = dir.join(format!("brc-output-{id}.txt"));
+    let plain = dir.join(format!("brc-output-{id}.out"));
+    let gzipped = dir.join(format!("brc-output-{id}.out.gz"));
+    let rows = (0..40_000u32)
+        .map(|i| format!("Station {};{}.{}\n", i % 9_000, i % 70, i % 10))
+        .collect::<String>();
+    fs::write(&input, rows).unwrap();
+
+    let input = input.to_str().unwrap();
+    let stdout = run(&[input]);
+    assert!(run(&["--output", plain.to_str().unwrap(), input]).is_empty());
+    assert!(run(&["--output", gzipped.to_str().unwrap(), input]).is_empty());
+
+    let unzipped = Command::new("gzip")
+        .arg("-dc")
+        .arg(&gzipped)
+        .output()
+        .expect("failed to run gzip");
+    assert!(unzipped.status.success());
+    let compressed = fs::metadata(&gzipped).unwrap().len();
+
+    assert_eq!(fs::read(&plain).unwrap(), stdout);
+    assert_eq!(unzipped.stdout, stdout);
+    assert!(compressed < stdout.len() as u64 / 2, "{compressed} bytes");
+    for path in [input.as_ref(), plain.as_path(), gzipped.as_path()] {
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,36 @@
+use std::{fs, process::Command};
+
+fn run(args: &[&str]) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(
+        output.status.success(),
+        "brc-rust exited with {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    output.stdout
+}
+
+/// Output spanning many flushes of the smallest buffers is written the same
+/// whatever their size.
+#[test]
+fn output_is_identical_across_buffer_sizes() {
+    let input = (0..5000)
+        .map(|i| format!("Station {i};{}.{}\n", i % 90, i % 10))
+        .collect::<String>();
+    let path = std::env::temp_dir().join(format!("brc-buffer-size-{}.txt", std::process::id()));
+    fs::write(&path, input).unwrap();
+    let path = path.to_str().unwrap();
+
+    let default = run(&[path]);
+    assert!(default.len() > 100_000);
+    for size in ["0", "1", "7", "4096", "16777216"] {
+        let output = run(&["--output-buffer-size", size, path]);
+        assert!(output == default, "--output-buffer-size {size}");
+    }
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,217 @@
+use std::{fs, path::Path, process::Command};
+
+fn fixture(name: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn run(args: &[&str]) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(
+        output.status.success(),
+        "brc-rust exited with {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    output.stdout
+}
+
+#[test]
+fn values_parse_to_exact_tenths() {
+    let cases = [
+        ("0.0", "0.0"),
+        ("-0.5", "-0.5"),
+        ("9.8", "9.8"),
+        ("-99.9", "-99.9"),
+        ("123.4", "123.4"),
+        ("-1234.5", "-1234.5"),
+        (".7", "0.7"),
+        ("5", "5.0"),
+        ("12.34", "12.3"),
+    ];
+
+    let path = std::env::temp_dir().join(format!("brc-values-{}.txt", std::process::id()));
+    for (value, expected) in cases {
+        fs::write(&path, format!("S;{value}\n")).unwrap();
+        let output = run(&[path.to_str().unwrap()]);
+        assert_eq!(
+            String::from_utf8_lossy(&output),
+            format!("{{S={expected}/{expected}/{expected}}}"),
+            "{value}"
+        );
+    }
+    fs::remove_file(&path).unwrap();
+}
+
+/// A last line cut short before its delimiter or its value has nothing to
+/// record, rather than a station with an empty name or a 0.0 value.
+#[test]
+fn truncated_last_line_is_malformed() {
+    for (name, line) in [
+        ("truncated_name.txt", "abc"),
+        ("truncated_value.txt", "Baku;"),
+    ] {
+        for flag in ["--validate", "--threads=1"] {
+            let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+                .args([flag, "--verbose", &fixture(name)])
+                .output()
+                .expect("failed to run brc-rust");
+            assert!(output.status.success(), "{name} {flag}");
+            assert_eq!(
+                String::from_utf8_lossy(&output.stdout),
+                "{Abha=1.5/1.5/1.5, Baku=-2.5/-2.5/-2.5}",
+                "{name} {flag}"
+            );
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            assert!(
+                stderr.contains("lines: 2, malformed: 1, "),
+                "{name} {flag}: {stderr}"
+            );
+        }
+
+        let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+            .args(["--strict", &fixture(name)])
+            .output()
+            .expect("failed to run brc-rust");
+        assert!(!output.status.success(), "{name}");
+        assert!(output.stdout.is_empty(), "{name}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains(&format!("malformed line at byte 19: {line}")),
+            "{name}: {stderr}"
+        );
+    }
+}
+
+#[test]
+fn blanks_trailing_values_are_skipped() {
+    let output = run(&[&fixture("trailing_blanks.txt")]);
+
+    assert_eq!(
+        String::from_utf8_lossy(&output),
+        "{Bar=0.5/0.5/0.5, Foo=-2.3/5.0/12.3}"
+    );
+}
+
+#[test]
+fn mixed_line_endings_are_aggregated_alike() {
+    let path = fixture("mixed_endings.txt");
+    for args in [
+        &["--small-file-threshold", "0", "--threads", "1"][..],
+        &["--small-file-threshold", "0", "--threads", "3"],
+        &["--thousands-sep", ","],
+    ] {
+        let output = run(&[args, &[&path]].concat());
+        assert_eq!(
+            String::from_utf8_lossy(&output),
+            "{Bar=-1.5/-0.5/0.5, Foo=-2.3/5.0/12.3}",
+            "{args:?}"
+        );
+    }
+}
+
+/// `.5` and `-.5` read as `0.5` and `-0.5`, by the loose parser too
+#[test]
+fn leading_decimal_point_reads_as_zero() {
+    let path = fixture("leading_decimal.txt");
+    for args in [&[][..], &["--thousands-sep", "_"], &["--threads=2"]] {
+        let split = ["--small-file-threshold", "0"];
+        let output = run(&[args, &split, &[&path]].concat());
+        assert_eq!(
+            String::from_utf8_lossy(&output),
+            "{Bar=-0.5/-0.5/-0.5, Foo=0.5/1.0/1.5}",
+            "{args:?}"
+        );
+    }
+}
+
+/// Separators are dropped from the values, a value beyond `i16` tenths is
+/// malformed like any other
+#[test]
+fn thousands_separators_are_stripped() {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(["--thousands-sep", ",", "--verbose"])
+        .arg(fixture("thousands_sep.txt"))
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(output.status.success());
+
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "{Bar=12.5/12.5/12.5, Foo=-1000.0/117.3/1234.5}"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("lines: 3, malformed: 1, "), "{stderr}");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(["--thousands-sep", ",", "--delim", ","])
+        .arg(fixture("thousands_sep.txt"))
+        .output()
+        .expect("failed to run brc-rust");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("--thousands-sep can't be the delimiter"),
+        "{stderr}"
+    );
+}
+
+/// Integers in hundredths, the header isn't a line of its own
+#[test]
+fn scale_header_sets_the_values_scale() {
+    for threads in ["1", "3"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+            .args(["--small-file-threshold", "0", "--threads", threads])
+            .args(["--verbose", &fixture("scale.txt")])
+            .output()
+            .expect("failed to run brc-rust");
+        assert!(output.status.success(), "{threads} threads");
+
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "{Abha=-4.5/3.9/12.3, Baku=0.2/0.2/0.2}",
+            "{threads} threads"
+        );
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("lines: 3, malformed: 0, "),
+            "{threads} threads: {stderr}"
+        );
+    }
+}
+
+/// Runs of identical lines, a line whose value extends the previous one's and
+/// a repeat cut short by the end of the file
+#[test]
+fn rle_folds_repeated_lines_into_the_same_aggregates() {
+    let input = "Abha;1.0\nAbha;1.0\nAbha;1.0\nBaku;-2.5\nBaku;-2.5\nAbha;1.0\n\
+                 Abha;10.0\nAbha;10.0\nBaku;-2.5";
+    let path = std::env::temp_dir().join(format!("brc-rle-{}.txt", std::process::id()));
+    fs::write(&path, input).unwrap();
+    let path = path.to_str().unwrap();
+
+    for threads in ["--single-thread", "--threads=3"] {
+        let split = [
+            threads,
+            "--small-file-threshold",
+            "0",
+            "--format",
+            "partial",
+        ];
+        let output = run(&[&["--rle"][..], &split, &[path]].concat());
+        assert_eq!(
+            String::from_utf8_lossy(&output),
+            "Abha;1.0;10.0;24.0;6\nBaku;-2.5;-2.5;-7.5;3\n",
+            "{threads}"
+        );
+        assert_eq!(output, run(&[&split[..], &[path]].concat()), "{threads}");
+    }
+    fs::remove_file(path).unwrap();
+}
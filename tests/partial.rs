@@ -0,0 +1,104 @@
+use std::{env, fs, path::Path, process::Command};
+
+fn run(args: &[&str]) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(
+        output.status.success(),
+        "brc-rust exited with {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    output.stdout
+}
+
+#[test]
+fn merged_partials_match_combined_input() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let input = fs::read_to_string(fixtures.join("measurements.txt")).unwrap();
+    let golden = fs::read(fixtures.join("measurements.golden")).unwrap();
+
+    // Split the input in two halves and aggregate each on its own
+    let lines = input.lines().collect::<Vec<_>>();
+    let (first, second) = lines.split_at(lines.len() / 2);
+    let dir = env::temp_dir();
+    let mut partials = Vec::new();
+    for (idx, half) in [first, second].into_iter().enumerate() {
+        let path = dir.join(format!("brc-partial-{}-{idx}.txt", std::process::id()));
+        fs::write(&path, half.join("\n") + "\n").unwrap();
+        let partial = run(&["--format", "partial", path.to_str().unwrap()]);
+        fs::write(&path, partial).unwrap();
+        partials.push(path);
+    }
+
+    let mut args = vec!["--merge-partials"];
+    args.extend(partials.iter().map(|path| path.to_str().unwrap()));
+    let output = run(&args);
+    for path in partials {
+        fs::remove_file(path).unwrap();
+    }
+
+    assert_eq!(
+        String::from_utf8_lossy(&output),
+        String::from_utf8_lossy(&golden)
+    );
+}
+
+/// Partial files edited on Windows and names holding `;`, as a `--delim`
+/// other than `;` allows, read back like they were written.
+#[test]
+fn crlf_and_names_with_semicolons_round_trip() {
+    let dir = env::temp_dir();
+    let input = dir.join(format!("brc-partial-delim-{}.txt", std::process::id()));
+    fs::write(&input, "A;B,1.0\nC,2.0\nA;B,3.0\n").unwrap();
+    let expected = run(&["--delim", ",", input.to_str().unwrap()]);
+    assert_eq!(
+        String::from_utf8_lossy(&expected),
+        "{A;B=1.0/2.0/3.0, C=2.0/2.0/2.0}"
+    );
+
+    let partial = run(&[
+        "--delim",
+        ",",
+        "--format",
+        "partial",
+        input.to_str().unwrap(),
+    ]);
+    let partial = String::from_utf8(partial).unwrap().replace('\n', "\r\n");
+    fs::write(&input, partial).unwrap();
+    let output = run(&["--merge-partials", input.to_str().unwrap()]);
+    fs::remove_file(&input).unwrap();
+
+    assert_eq!(
+        String::from_utf8_lossy(&output),
+        String::from_utf8_lossy(&expected)
+    );
+}
+
+/// A station repeated within one partial file, as when partial files are
+/// concatenated, adds up rather than keeping only its last line.
+#[test]
+fn repeated_station_in_a_partial_file_adds_up() {
+    let path = env::temp_dir().join(format!("brc-partial-repeated-{}.txt", std::process::id()));
+    fs::write(
+        &path,
+        "Abha;-1.0;2.0;3.0;3\nBaku;0.5;0.5;0.5;1\nAbha;-4.0;1.0;-3.0;2\n",
+    )
+    .unwrap();
+
+    let output = run(&[
+        "--merge-partials",
+        "--format",
+        "partial",
+        path.to_str().unwrap(),
+    ]);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        String::from_utf8_lossy(&output),
+        "Abha;-4.0;2.0;0.0;5\nBaku;0.5;0.5;0.5;1\n"
+    );
+}
@@ -0,0 +1,94 @@
+use std::{collections::BTreeMap, env, fs, process::Command};
+
+const STATIONS: &[&str] = &["Abha", "Bulawayo", "Zürich"];
+
+fn tenths(val: i32) -> String {
+    let sign = if val < 0 { "-" } else { "" };
+    format!("{sign}{}.{}", val.abs() / 10, val.abs() % 10)
+}
+
+/// The nearest rank percentile of sorted `vals`
+fn nearest_rank(vals: &[i32], percentile: f64) -> i32 {
+    let rank = (percentile / 100. * vals.len() as f64).ceil() as usize;
+    vals[rank.max(1) - 1]
+}
+
+#[test]
+fn percentiles_match_sorted_values() {
+    let mut state: u64 = 0x9e7c;
+    let mut next = || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 33) as usize
+    };
+    let mut rows = (0..3_001)
+        .map(|_| {
+            let station = STATIONS[next() % STATIONS.len()];
+            (station, (next() % 1999) as i32 - 999)
+        })
+        .collect::<Vec<_>>();
+    // A station with a single value
+    rows.push(("Jos", -42));
+
+    let path = env::temp_dir().join(format!("brc-percentiles-{}.txt", std::process::id()));
+    let input = rows
+        .iter()
+        .map(|(station, val)| format!("{station};{}\n", tenths(*val)))
+        .collect::<String>();
+    fs::write(&path, input).unwrap();
+
+    let mut by_station = BTreeMap::<&str, Vec<i32>>::new();
+    for &(station, val) in &rows {
+        by_station.entry(station).or_default().push(val);
+    }
+    let expected = by_station
+        .into_iter()
+        .map(|(station, mut vals)| {
+            vals.sort();
+            // Rounded half up
+            let (sum, count) = (vals.iter().sum::<i32>(), vals.len() as i32);
+            let mean = tenths((2 * sum + count).div_euclid(2 * count));
+            let (min, max) = (vals[0], vals[vals.len() - 1]);
+            let (p50, p95) = (nearest_rank(&vals, 50.), nearest_rank(&vals, 95.));
+            format!(
+                "{station}={}/{mean}/{} [p50={} p95={}]",
+                tenths(min),
+                tenths(max),
+                tenths(p50),
+                tenths(p95)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let expected = format!("{{{expected}}}");
+
+    for threads in ["1", "3"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+            .args(["--percentiles=50,95", "--small-file-threshold", "0"])
+            .args(["--threads", threads])
+            .arg(&path)
+            .output()
+            .expect("failed to run brc-rust");
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            expected,
+            "{threads} threads"
+        );
+    }
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn percentiles_out_of_range_are_an_error() {
+    for arg in ["0", "50,101", "median"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+            .args(["--percentiles", arg, "unused.txt"])
+            .output()
+            .expect("failed to run brc-rust");
+        assert!(!output.status.success(), "{arg}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("--percentiles"), "{arg}: {stderr}");
+    }
+}
@@ -0,0 +1,35 @@
+use std::{path::Path, process::Command};
+
+fn fixture(name: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn run(args: &[&str]) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(
+        output.status.success(),
+        "brc-rust exited with {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    output.stdout
+}
+
+#[test]
+fn pivot_aggregates_keys_and_subkeys() {
+    let output = run(&["--pivot", &fixture("pivot.txt")]);
+
+    assert_eq!(
+        String::from_utf8_lossy(&output),
+        "{France=10.0/14.0/20.0, France/Lyon=20.0/20.0/20.0, France/Paris=10.0/11.0/12.0, \
+         Germany=-5.0/0.0/5.0, Germany/Berlin=-5.0/-5.0/-5.0, Germany/Bonn=5.0/5.0/5.0}"
+    );
+}
@@ -0,0 +1,138 @@
+use std::{env, fs, path::Path, process::Command};
+
+/// Splits a sample line into its metric name, unescaped label values and
+/// value, failing on anything outside the text exposition format.
+fn parse_sample(line: &str) -> (&str, Vec<(&str, String)>, f64) {
+    let name_end = line.find(['{', ' ']).unwrap();
+    let (name, mut rest) = line.split_at(name_end);
+    assert!(name
+        .chars()
+        .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == ':'));
+
+    let mut labels = Vec::new();
+    if let Some(body) = rest.strip_prefix('{') {
+        let mut chars = body.char_indices();
+        loop {
+            let (start, _) = chars.next().unwrap();
+            let eq = body[start..].find("=\"").unwrap() + start;
+            let label = &body[start..eq];
+            chars.nth(eq - start);
+
+            let mut value = String::new();
+            loop {
+                match chars.next().unwrap() {
+                    (_, '\\') => match chars.next().unwrap().1 {
+                        '\\' => value.push('\\'),
+                        '"' => value.push('"'),
+                        'n' => value.push('\n'),
+                        other => panic!("invalid escape \\{other} in {line}"),
+                    },
+                    (_, '"') => break,
+                    (_, ch) => value.push(ch),
+                }
+            }
+            labels.push((label, value));
+
+            match chars.next().unwrap() {
+                (_, ',') => continue,
+                (end, '}') => {
+                    rest = &body[end + 1..];
+                    break;
+                }
+                (_, other) => panic!("unexpected {other} in {line}"),
+            }
+        }
+    }
+
+    let value = rest.strip_prefix(' ').unwrap().parse().unwrap();
+    (name, labels, value)
+}
+
+#[test]
+fn prometheus_output_is_valid_exposition() {
+    let input = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/prometheus.txt");
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(["--format", "prometheus"])
+        .arg(input)
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(output.status.success());
+    let output = String::from_utf8(output.stdout).unwrap();
+
+    let mut samples = Vec::new();
+    let mut families = Vec::<&str>::new();
+    for line in output.lines() {
+        if let Some(comment) = line.strip_prefix("# ") {
+            assert!(comment.starts_with("HELP ") || comment.starts_with("TYPE "));
+            continue;
+        }
+
+        let sample = parse_sample(line);
+        // Samples of a family are consecutive
+        if families.last() != Some(&sample.0) {
+            assert!(!families.contains(&sample.0), "{} is split", sample.0);
+            families.push(sample.0);
+        }
+        samples.push(sample);
+    }
+
+    let station = |metric: &str, station: &str| {
+        samples
+            .iter()
+            .find(|(name, labels, _)| {
+                *name == metric && labels == &[("station", station.to_string())]
+            })
+            .map(|sample| sample.2)
+    };
+    assert_eq!(station("brc_station_mean", "Say \"Hi\"\\Back"), Some(1.));
+    assert_eq!(station("brc_station_min", "Plain"), Some(-2.5));
+    // Rounded like the text output, half up
+    assert_eq!(station("brc_station_mean", "Plain"), Some(-0.2));
+    assert_eq!(station("brc_station_count", "Plain"), Some(2.));
+
+    let total = |metric: &str| samples.iter().find(|s| s.0 == metric).map(|s| s.2);
+    assert_eq!(total("brc_total_rows"), Some(3.));
+    assert_eq!(total("brc_distinct_stations"), Some(2.));
+}
+
+fn run(name: &str, input: &[u8], args: &[&str]) -> String {
+    let path = env::temp_dir().join(format!("brc-prometheus-{}-{name}.txt", std::process::id()));
+    fs::write(&path, input).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(["--format", "prometheus"])
+        .args(args)
+        .arg(&path)
+        .output()
+        .expect("failed to run brc-rust");
+    fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn mean_follows_rounding_mode() {
+    let input = b"Plain;-2.5\nPlain;2.0\n";
+    for (mode, mean) in [("nearest", "-0.2"), ("floor", "-0.3"), ("trunc", "-0.2")] {
+        let output = run(mode, input, &["--rounding", mode]);
+        assert!(
+            output.contains(&format!("brc_station_mean{{station=\"Plain\"}} {mean}\n")),
+            "{mode}: {output}"
+        );
+    }
+}
+
+/// Names that aren't valid UTF-8 are labelled with replacement characters
+#[test]
+fn invalid_utf8_names_are_replaced() {
+    let output = run("invalid", b"x\xffy;1.0\n", &[]);
+
+    let (_, labels, value) = output
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(parse_sample)
+        .find(|sample| sample.0 == "brc_station_max")
+        .unwrap();
+    assert_eq!(labels, [("station", "x\u{fffd}y".to_string())]);
+    assert_eq!(value, 1.);
+}
@@ -0,0 +1,61 @@
+use std::{collections::HashSet, env, fs, process::Command};
+
+/// Above the 50_000 results `sort::RADIX_SORT_THRESHOLD` sorts with
+const STATIONS: usize = 60_000;
+
+/// Distinct random names over a few bytes in no particular order, many of
+/// them prefixes of others and some not UTF-8
+fn names(seed: u64) -> Vec<Vec<u8>> {
+    let mut state = seed.wrapping_mul(0x9e3779b97f4a7c15) | 1;
+    let mut next = || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 33) as usize
+    };
+
+    let alphabet = b"abz \xc3\xa9\xff";
+    let mut seen = HashSet::new();
+    let mut names = Vec::new();
+    while names.len() < STATIONS {
+        let len = 1 + next() % 12;
+        let name = (0..len)
+            .map(|_| alphabet[next() % alphabet.len()])
+            .collect::<Vec<_>>();
+        if seen.insert(name.clone()) {
+            names.push(name);
+        }
+    }
+
+    names
+}
+
+#[test]
+fn radix_sort_matches_comparison_sort() {
+    let mut names = names(7);
+    let mut input = Vec::new();
+    for name in &names {
+        input.extend(name);
+        input.extend(b";1.0\n");
+    }
+    let path = env::temp_dir().join(format!("brc-radix-{}.txt", std::process::id()));
+    fs::write(&path, input).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(["--format", "partial"])
+        .arg(&path)
+        .output()
+        .expect("failed to run brc-rust");
+    fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+
+    let sorted = output
+        .stdout
+        .strip_suffix(b"\n")
+        .unwrap()
+        .split(|&ch| ch == b'\n')
+        .map(|line| line.rsplitn(5, |&ch| ch == b';').last().unwrap().to_vec())
+        .collect::<Vec<_>>();
+    names.sort_unstable_by(|a, b| a.as_slice().cmp(b.as_slice()));
+    assert!(sorted == names);
+}
@@ -0,0 +1,66 @@
+use std::{
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+/// Runs `--repl` with `args` over the fixture `name`, answering `queries`.
+fn repl(args: &[&str], name: &str, queries: &[u8]) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .arg("--repl")
+        .args(args)
+        .arg(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/fixtures")
+                .join(name),
+        )
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("failed to run brc-rust");
+    child.stdin.take().unwrap().write_all(queries).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn repl_answers_scripted_queries() {
+    let queries = b"count\nAbha\n\ntop 1\ntop 5\nNowhere\ntop two\nquit\ncount\n";
+    assert_eq!(
+        repl(&[], "three_lines.txt", queries),
+        "2\n\
+         Abha=1.0/2.0/3.0 (2 measurements)\n\
+         Abha=1.0/2.0/3.0\n\
+         Abha=1.0/2.0/3.0\n\
+         Baku=-2.5/-2.5/-2.5\n\
+         unknown station: Nowhere\n\
+         usage: top <n>\n"
+    );
+}
+
+/// Means rounded by `--rounding` as in the output, seeded stations without
+/// values left out of `top`
+#[test]
+fn repl_rounds_like_the_output_and_ranks_measured_stations() {
+    let seeds = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/seed_stations.txt");
+    let args = [
+        "--rounding",
+        "floor",
+        "--seed-stations",
+        seeds.to_str().unwrap(),
+    ];
+    let queries = b"count\ntop 10\nE\nNowhere\n";
+
+    assert_eq!(
+        repl(&args, "half_tenths.txt", queries),
+        "6\n\
+         D=2.2/2.2/2.3\n\
+         F=0.1/0.1/0.2\n\
+         E=-2.3/-2.3/-2.2\n\
+         E=-2.3/-2.3/-2.2 (2 measurements)\n\
+         Nowhere=NA/NA/NA (0 measurements)\n"
+    );
+}
@@ -0,0 +1,54 @@
+use std::{path::Path, process::Command};
+
+fn fixture(name: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn run(args: &[&str]) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(
+        output.status.success(),
+        "brc-rust exited with {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    output.stdout
+}
+
+#[test]
+fn rounding_modes_on_half_tenths() {
+    for (mode, expected) in [
+        (
+            "nearest",
+            "{A=0.1/0.2/0.2, B=-0.2/-0.1/-0.1, C=1.0/1.1/1.1}",
+        ),
+        ("ceil", "{A=0.1/0.2/0.2, B=-0.2/-0.1/-0.1, C=1.0/1.1/1.1}"),
+        ("floor", "{A=0.1/0.1/0.2, B=-0.2/-0.2/-0.1, C=1.0/1.0/1.1}"),
+        ("trunc", "{A=0.1/0.1/0.2, B=-0.2/-0.1/-0.1, C=1.0/1.0/1.1}"),
+    ] {
+        let output = run(&["--rounding", mode, &fixture("rounding.txt")]);
+
+        assert_eq!(String::from_utf8_lossy(&output), expected, "{mode}");
+    }
+}
+
+#[test]
+fn default_rounding_is_half_up() {
+    // `{:.1}` of the float means gives D=2.2 (2.25 is held exactly and ties
+    // round to even) and F=0.1 (0.15 is held just below the half)
+    let expected = "{D=2.2/2.3/2.3, E=-2.3/-2.2/-2.2, F=0.1/0.2/0.2}";
+
+    let output = run(&[&fixture("half_tenths.txt")]);
+    assert_eq!(String::from_utf8_lossy(&output), expected);
+
+    let output = run(&["--reference-compat", &fixture("half_tenths.txt")]);
+    assert_eq!(String::from_utf8_lossy(&output), format!("{expected}\n"));
+}
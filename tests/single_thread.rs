@@ -0,0 +1,79 @@
+use std::{env, fs, process::Command};
+
+const SEEDS: u64 = 20;
+
+/// A random dataset from `seed`: up to a few hundred stations, some with
+/// multibyte names, and values across the whole -99.9..=99.9 range.
+fn generate(seed: u64) -> String {
+    let mut state = seed.wrapping_mul(0x9e3779b97f4a7c15) | 1;
+    let mut next = || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 33) as usize
+    };
+
+    let stations = 1 + next() % 300;
+    let names = (0..stations)
+        .map(|idx| {
+            let letters = ["a", "Z", "é", "ø", "東", " ", "'"];
+            let len = 1 + next() % 12;
+            let tail = (0..len)
+                .map(|_| letters[next() % letters.len()])
+                .collect::<String>();
+            format!("S{idx}{tail}")
+        })
+        .collect::<Vec<_>>();
+
+    let rows = next() % 20_000;
+    (0..rows)
+        .map(|_| {
+            let name = &names[next() % names.len()];
+            let val = (next() % 1999) as i32 - 999;
+            let sign = if val < 0 { "-" } else { "" };
+            format!("{name};{sign}{}.{}\n", val.abs() / 10, val.abs() % 10)
+        })
+        .collect()
+}
+
+fn run(args: &[&str], path: &std::path::Path) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .arg(path)
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn single_thread_matches_workers() {
+    for seed in 0..SEEDS {
+        let path = env::temp_dir().join(format!("brc-single-{}-{seed}.txt", std::process::id()));
+        fs::write(&path, generate(seed)).unwrap();
+
+        let single = run(&["--single-thread"], &path);
+        for threads in ["2", "5"] {
+            let workers = run(
+                &["--threads", threads, "--small-file-threshold", "0"],
+                &path,
+            );
+            assert_eq!(single, workers, "seed {seed}, {threads} threads");
+        }
+        fs::remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+fn single_thread_conflicts_with_threads() {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(["--single-thread", "--threads", "2", "unused.txt"])
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--single-thread"));
+}
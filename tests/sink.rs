@@ -0,0 +1,58 @@
+use std::io;
+
+use brc_rust::{ResultSink, StationResult};
+
+/// Records the calls it gets, a station by its name, count and mean
+#[derive(Default)]
+struct Recorder(Vec<String>);
+
+impl ResultSink for Recorder {
+    fn begin(&mut self, stations: usize) -> io::Result<()> {
+        self.0.push(format!("begin {stations}"));
+        Ok(())
+    }
+
+    fn write_station(&mut self, station: &StationResult) -> io::Result<()> {
+        let stats = station.stats();
+        let name = String::from_utf8_lossy(station.name);
+        self.0
+            .push(format!("{name} {} {:.2}", stats.count, stats.mean));
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.0.push("finish".to_string());
+        Ok(())
+    }
+}
+
+/// The same calls whether the lines are parsed on the calling thread or split
+/// between workers, which a requested count does for small inputs too
+#[test]
+fn stations_are_written_between_begin_and_finish() {
+    let data = "Hamburg;12.0\nBulawayo;8.9\nHamburg;34.2\nAbha;-1.5\n".repeat(30);
+    for threads in [1, 3] {
+        let mut recorder = Recorder::default();
+        brc_rust::aggregate_into(data.as_bytes(), threads, &mut recorder).unwrap();
+
+        assert_eq!(
+            recorder.0,
+            [
+                "begin 3",
+                "Abha 30 -1.50",
+                "Bulawayo 30 8.90",
+                "Hamburg 60 23.10",
+                "finish"
+            ],
+            "{threads} threads"
+        );
+    }
+}
+
+#[test]
+fn empty_input_still_begins_and_finishes() {
+    let mut recorder = Recorder::default();
+    brc_rust::aggregate_into(b"", 1, &mut recorder).unwrap();
+
+    assert_eq!(recorder.0, ["begin 0", "finish"]);
+}
@@ -0,0 +1,174 @@
+use std::{collections::BTreeMap, env, fs, process::Command};
+
+const ROWS: usize = 5_000;
+const STATIONS: &[&str] = &[
+    "Abha",
+    "Bulawayo",
+    "Hamburg",
+    "Las Palmas de Gran Canaria",
+    "São Paulo",
+    "St. John's",
+    "Zürich",
+];
+
+/// Deterministic input: stations and values in tenths drawn from a small LCG.
+fn generate() -> Vec<(&'static str, i32)> {
+    let mut state: u64 = 0x5eed;
+    let mut next = || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 33) as usize
+    };
+
+    (0..ROWS)
+        .map(|_| {
+            let station = STATIONS[next() % STATIONS.len()];
+            let val = (next() % 1999) as i32 - 999;
+            (station, val)
+        })
+        .collect()
+}
+
+fn tenths(val: i32) -> String {
+    let sign = if val < 0 { "-" } else { "" };
+    format!("{sign}{}.{}", val.abs() / 10, val.abs() % 10)
+}
+
+#[test]
+fn generated_input_aggregates() {
+    let rows = generate();
+
+    let path = env::temp_dir().join(format!("brc-smoke-{}.txt", std::process::id()));
+    let input = rows
+        .iter()
+        .map(|(station, val)| format!("{station};{}\n", tenths(*val)))
+        .collect::<String>();
+    fs::write(&path, input).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .arg(&path)
+        .output()
+        .expect("failed to run brc-rust");
+    fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+    let output = String::from_utf8(output.stdout).unwrap();
+
+    let mut expected = BTreeMap::<&str, (i32, i32, i64, u32)>::new();
+    for &(station, val) in &rows {
+        let entry = expected.entry(station).or_insert((val, val, 0, 0));
+        entry.0 = entry.0.min(val);
+        entry.1 = entry.1.max(val);
+        entry.2 += val as i64;
+        entry.3 += 1;
+    }
+
+    assert_eq!(output.matches('=').count(), STATIONS.len(), "{output}");
+    for (station, (min, max, sum, count)) in expected {
+        let mean = sum as f64 / count as f64 / 10.;
+        let prefix = format!("{station}={}/", tenths(min));
+        let suffix = format!("/{}", tenths(max));
+
+        let entry = output
+            .split(", ")
+            .map(|entry| entry.trim_matches(['{', '}']))
+            .find(|entry| entry.starts_with(&prefix) && entry.ends_with(&suffix))
+            .unwrap_or_else(|| panic!("no {prefix}…{suffix} in {output}"));
+        let reported = entry[prefix.len()..entry.len() - suffix.len()]
+            .parse::<f64>()
+            .unwrap();
+        assert!(
+            (reported - mean).abs() <= 0.05 + 1e-9,
+            "{station}: {reported} vs {mean}"
+        );
+    }
+}
+
+#[test]
+fn sums_past_i32_keep_the_mean() {
+    const ROWS: usize = 2_200_000;
+    let vals = [999i64, 997];
+
+    let path = env::temp_dir().join(format!("brc-smoke-sum-{}.txt", std::process::id()));
+    let input = (0..ROWS)
+        .map(|i| {
+            if i % 2 == 0 {
+                "Hot;99.9\n"
+            } else {
+                "Hot;99.7\n"
+            }
+        })
+        .collect::<String>();
+    fs::write(&path, input).unwrap();
+
+    let sum = (0..ROWS).map(|i| vals[i % 2]).sum::<i64>();
+    assert!(sum > i32::MAX as i64);
+    let expected = format!("{{Hot=99.7/{:.1}/99.9}}", sum as f64 / ROWS as f64 / 10.);
+
+    for threads in ["1", "2"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+            .args(["--small-file-threshold", "0", "--threads", threads])
+            .arg(&path)
+            .output()
+            .expect("failed to run brc-rust");
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            expected,
+            "{threads} threads"
+        );
+    }
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn more_stations_than_slots_are_all_kept() {
+    const STATIONS: u32 = 9_000;
+
+    let input = (0..STATIONS * 2)
+        .map(|i| format!("Station {};{}.{}\n", i % STATIONS, i % 50, i % 10))
+        .collect::<String>();
+    let stations = brc_rust::aggregate(input.as_bytes(), 1);
+    assert_eq!(stations.len(), STATIONS as usize);
+    for i in 0..STATIONS {
+        let name = format!("Station {i}");
+        let (_, stats) = stations
+            .iter()
+            .find(|(station, _)| *station == name.as_bytes())
+            .unwrap_or_else(|| panic!("{name} missing"));
+        assert_eq!(stats.count, 2, "{name}");
+    }
+
+    // Growing the tables the workers' results are merged into as well, and
+    // the workers' own keeping their alignment
+    let path = env::temp_dir().join(format!("brc-smoke-stations-{}.txt", std::process::id()));
+    fs::write(&path, &input).unwrap();
+    for align in [&[][..], &["--align-tables"]] {
+        let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+            .args(["--small-file-threshold", "0", "--threads", "3"])
+            .args(align)
+            .arg(&path)
+            .output()
+            .expect("failed to run brc-rust");
+        assert!(output.status.success());
+        let output = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(output.matches('=').count(), STATIONS as usize, "{align:?}");
+    }
+    fs::remove_file(&path).unwrap();
+}
+
+/// A requested worker count is used for inputs below the small file
+/// threshold too, splitting the lines between the workers
+#[test]
+fn aggregate_splits_small_inputs_between_requested_workers() {
+    let input = (0..1_000)
+        .map(|i| format!("Station {};{}.{}\n", i % 7, i % 40, i % 10))
+        .collect::<String>();
+
+    let single = brc_rust::aggregate(input.as_bytes(), 1);
+    assert_eq!(single.len(), 7);
+    for threads in [2, 4, 16] {
+        let stations = brc_rust::aggregate(input.as_bytes(), threads);
+        assert_eq!(stations, single, "{threads} threads");
+    }
+}
@@ -0,0 +1,51 @@
+use std::{path::Path, process::Command};
+
+fn fixture(name: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn run(args: &[&str]) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(
+        output.status.success(),
+        "brc-rust exited with {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    output.stdout
+}
+
+#[test]
+fn sort_orders_of_accented_names() {
+    let bytes = "{Abha=3.0/3.0/3.0, Lyon=7.0/7.0/7.0, Oslo=6.0/6.0/6.0, Zürich=1.0/1.0/1.0, \
+        Ávila=-5.0/0.0/5.0, Óbidos=2.0/2.0/2.0, Łódź=4.0/4.0/4.0}";
+    let unicode = "{Abha=3.0/3.0/3.0, Ávila=-5.0/0.0/5.0, Łódź=4.0/4.0/4.0, Lyon=7.0/7.0/7.0, \
+        Óbidos=2.0/2.0/2.0, Oslo=6.0/6.0/6.0, Zürich=1.0/1.0/1.0}";
+
+    let path = fixture("accented_names.txt");
+    for threads in ["1", "3"] {
+        for (sort, expected) in [
+            (None, bytes),
+            (Some("--sort=bytes"), bytes),
+            (Some("--sort=unicode"), unicode),
+        ] {
+            let mut args = vec!["--small-file-threshold", "0", "--threads", threads];
+            args.extend(sort);
+            args.push(&path);
+            let output = run(&args);
+            assert_eq!(
+                String::from_utf8_lossy(&output),
+                expected,
+                "{sort:?}, {threads} threads"
+            );
+        }
+    }
+}
@@ -0,0 +1,124 @@
+#![cfg(feature = "sqlite")]
+
+use std::{env, fs, path::Path, process::Command};
+
+use rusqlite::Connection;
+
+fn write_db(name: &str, args: &[&str]) -> std::path::PathBuf {
+    let path = env::temp_dir().join(format!("brc-sqlite-{}-{name}.db", std::process::id()));
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(["--format", "sqlite", "--output"])
+        .arg(&path)
+        .args(args)
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    path
+}
+
+#[test]
+fn database_holds_the_stations() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let input = fixtures.join("measurements.txt");
+    let path = write_db("fixture", &[input.to_str().unwrap()]);
+
+    let db = Connection::open(&path).unwrap();
+    let rows = db
+        .prepare(
+            "SELECT name, min, round(mean, 1), max, count FROM stations \
+             WHERE name IN ('Abha', 'Accra') ORDER BY name",
+        )
+        .unwrap()
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, f64>(1)?,
+                row.get::<_, f64>(2)?,
+                row.get::<_, f64>(3)?,
+                row.get::<_, i64>(4)?,
+            ))
+        })
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let count: i64 = db
+        .query_row("SELECT count(*) FROM stations", [], |row| row.get(0))
+        .unwrap();
+    drop(db);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        rows,
+        [
+            ("Abha".to_string(), -77.2, -9.7, 99.9, 16),
+            ("Accra".to_string(), -63.5, 16.6, 98.0, 9)
+        ]
+    );
+    assert_eq!(count, 16);
+}
+
+#[test]
+fn large_database_passes_integrity_check() {
+    let input = env::temp_dir().join(format!("brc-sqlite-{}.txt", std::process::id()));
+    let rows = (0..50_000)
+        .map(|i| format!("Station {i};{}.{}\n", i % 100, i % 10))
+        .collect::<String>();
+    fs::write(&input, rows).unwrap();
+    let path = write_db("large", &[input.to_str().unwrap()]);
+    fs::remove_file(&input).unwrap();
+
+    let db = Connection::open(&path).unwrap();
+    let check: String = db
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .unwrap();
+    let row: (f64, i64) = db
+        .query_row(
+            "SELECT mean, count FROM stations WHERE name = 'Station 31337'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    drop(db);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(check, "ok");
+    assert_eq!(row, (37.7, 1));
+}
+
+/// Seeded stations without values have no extremes nor mean.
+#[test]
+fn stations_without_values_are_null() {
+    let dir = env::temp_dir();
+    let input = dir.join(format!("brc-sqlite-seeded-{}.txt", std::process::id()));
+    let seeds = dir.join(format!("brc-sqlite-seeds-{}.txt", std::process::id()));
+    fs::write(&input, "Abha;1.0\n").unwrap();
+    fs::write(&seeds, "Abha\nBaku\n").unwrap();
+    let path = write_db(
+        "seeded",
+        &[
+            "--seed-stations",
+            seeds.to_str().unwrap(),
+            input.to_str().unwrap(),
+        ],
+    );
+    fs::remove_file(&input).unwrap();
+    fs::remove_file(&seeds).unwrap();
+
+    let db = Connection::open(&path).unwrap();
+    let baku: (Option<f64>, Option<f64>, Option<f64>, i64) = db
+        .query_row(
+            "SELECT min, mean, max, count FROM stations WHERE name = 'Baku'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .unwrap();
+    drop(db);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(baku, (None, None, None, 0));
+}
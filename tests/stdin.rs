@@ -0,0 +1,51 @@
+use std::{
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+fn run_stdin(args: &[&str], input: &[u8]) -> std::process::Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run brc-rust");
+    child.stdin.take().unwrap().write_all(input).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn piped_input_matches_file_input() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/measurements.txt");
+    let input = std::fs::read(&path).unwrap();
+
+    for args in [&[][..], &["--small-file-threshold", "0", "--threads", "2"]] {
+        let from_file = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+            .args(args)
+            .arg(&path)
+            .output()
+            .expect("failed to run brc-rust");
+        assert!(from_file.status.success());
+
+        let from_stdin = run_stdin(&[args, &["-"]].concat(), &input);
+        assert!(
+            from_stdin.status.success(),
+            "{}",
+            String::from_utf8_lossy(&from_stdin.stderr)
+        );
+        assert_eq!(
+            String::from_utf8_lossy(&from_stdin.stdout),
+            String::from_utf8_lossy(&from_file.stdout),
+            "{args:?}"
+        );
+    }
+}
+
+#[test]
+fn stdin_is_rejected_where_it_is_read_twice() {
+    let output = run_stdin(&["--repl", "-"], b"Abha;1.0\n");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("stdin"));
+}
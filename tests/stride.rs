@@ -0,0 +1,57 @@
+use std::{env, fs, process::Command};
+
+fn run(name: &str, input: &str, args: &[&str]) -> std::process::Output {
+    let path = env::temp_dir().join(format!("brc-stride-{}-{name}.txt", std::process::id()));
+    fs::write(&path, input).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .arg(&path)
+        .output()
+        .expect("failed to run brc-rust");
+    fs::remove_file(&path).unwrap();
+
+    output
+}
+
+/// Every other line from the first is aggregated, counts and sums scaled by 2
+#[test]
+fn strided_counts_are_scaled() {
+    let input = "Abha;1.0\nAbha;3.0\nAbha;5.0\nBaku;2.0\nAbha;7.0\n";
+    let args = ["--single-thread", "--stride", "2", "--format", "partial"];
+    let output = run("scaled", input, &args);
+    assert!(output.status.success());
+
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Abha;1.0;7.0;26.0;6\n"
+    );
+}
+
+#[test]
+fn zero_stride_is_rejected() {
+    let output = run("zero", "Abha;1.0\n", &["--stride", "0"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--stride must be at least 1"));
+}
+
+/// Each of the two workers keeps its first line, scaling a count of 2 past
+/// `u32::MAX`
+#[test]
+fn overflowing_scaled_count_is_an_error() {
+    let args = [
+        "--small-file-threshold",
+        "0",
+        "--threads",
+        "2",
+        "--stride",
+        "4294967295",
+    ];
+    let output = run("overflow", "Abha;1.0\nAbha;3.0\n", &args);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "Abha: count scaled by --stride 4294967295 overflows\n"
+    );
+}
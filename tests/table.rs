@@ -0,0 +1,98 @@
+#![cfg(feature = "bench")]
+
+use brc_rust::{
+    bench::Table,
+    hasher::{Djb2, StationHasher},
+};
+
+const SLOTS: usize = 101;
+
+fn home(name: &[u8]) -> usize {
+    Djb2::hash(name) as usize % SLOTS
+}
+
+/// Two distinct names sharing a home slot
+fn colliding() -> (&'static [u8], &'static [u8]) {
+    let names = (0..)
+        .map(|i| format!("Station {i}").into_bytes().leak() as &'static [u8])
+        .take(SLOTS + 1)
+        .collect::<Vec<_>>();
+    for (idx, &a) in names.iter().enumerate() {
+        if let Some(&b) = names[idx + 1..].iter().find(|&&b| home(b) == home(a)) {
+            return (a, b);
+        }
+    }
+    unreachable!("more names than slots")
+}
+
+#[test]
+fn entry_fills_an_empty_slot() {
+    let mut table = Table::with_slots(SLOTS);
+    assert_eq!(table.add(b"Abha", 15), (home(b"Abha"), 0));
+
+    let stations = table.stations();
+    assert_eq!(stations.len(), 1);
+    let (name, stats, slot) = stations[0];
+    assert_eq!((name, slot), (&b"Abha"[..], home(b"Abha")));
+    assert_eq!(
+        (stats.min, stats.mean, stats.max, stats.count),
+        (1.5, 1.5, 1.5, 1)
+    );
+}
+
+#[test]
+fn entry_returns_the_existing_aggregate() {
+    let mut table = Table::with_slots(SLOTS);
+    table.add(b"Abha", 15);
+    assert_eq!(table.add(b"Abha", -5), (home(b"Abha"), 1));
+
+    let stations = table.stations();
+    assert_eq!(stations.len(), 1);
+    let stats = stations[0].1;
+    assert_eq!(
+        (stats.min, stats.mean, stats.max, stats.count),
+        (-0.5, 0.5, 1.5, 2)
+    );
+}
+
+#[test]
+fn entry_probes_past_a_colliding_slot() {
+    let (a, b) = colliding();
+    let (home, next) = (home(a), (home(a) + 1) % SLOTS);
+
+    let mut table = Table::with_slots(SLOTS);
+    assert_eq!(table.add(a, 10), (home, 0));
+    assert_eq!(table.add(b, 20), (next, 0));
+    assert_eq!(table.add(a, 30), (home, 1));
+    assert_eq!(table.add(b, 40), (next, 1));
+
+    let stations = table.stations();
+    let sums = stations
+        .iter()
+        .map(|&(name, stats, slot)| (name, stats.mean * 2., slot))
+        .collect::<Vec<_>>();
+    // In slot order, `next` wraps around to 0 for a home in the last slot
+    let mut expected = [(a, 4.0, home), (b, 6.0, next)];
+    expected.sort_unstable_by_key(|&(.., slot)| slot);
+    assert_eq!(sums, expected);
+}
+
+#[test]
+fn entries_survive_the_table_growing() {
+    let names = (0..20)
+        .map(|i| format!("Station {i}").into_bytes().leak() as &'static [u8])
+        .collect::<Vec<_>>();
+
+    let mut table = Table::with_slots(7);
+    for &name in names.iter().chain(&names) {
+        table.add(name, 10);
+    }
+    assert!(table.capacity() > names.len());
+
+    let mut stations = table.stations();
+    stations.sort_unstable_by_key(|(name, ..)| *name);
+    let mut sorted = names.clone();
+    sorted.sort_unstable();
+    assert_eq!(stations.iter().map(|s| s.0).collect::<Vec<_>>(), sorted);
+    assert!(stations.iter().all(|(_, stats, _)| stats.count == 2));
+}
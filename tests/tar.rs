@@ -0,0 +1,33 @@
+#![cfg(feature = "tar")]
+
+use std::{env, fs, path::Path, process::Command};
+
+#[test]
+fn tar_member_matches_plain_file() {
+    let input = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/measurements.txt");
+    let golden = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/measurements.golden");
+
+    let path = env::temp_dir().join(format!("brc-tar-{}.tar", std::process::id()));
+    let mut builder = tar::Builder::new(fs::File::create(&path).unwrap());
+    builder
+        .append_path_with_name(&golden, "data/other.txt")
+        .unwrap();
+    builder
+        .append_path_with_name(&input, "data/measurements.txt")
+        .unwrap();
+    builder.finish().unwrap();
+    drop(builder);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(["--tar-member", "data/measurements.txt"])
+        .arg(&path)
+        .output()
+        .expect("failed to run brc-rust");
+    fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        fs::read_to_string(golden).unwrap()
+    );
+}
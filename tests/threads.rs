@@ -0,0 +1,138 @@
+use std::{fs, path::Path, process::Command};
+
+use brc_rust::recommended_threads;
+
+const MIB: u64 = 1 << 20;
+
+fn fixture(name: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn cpus() -> usize {
+    std::thread::available_parallelism().unwrap().get()
+}
+
+#[test]
+fn tiny_files_get_a_single_thread() {
+    for size in [0, 1, 4096, MIB - 1] {
+        assert_eq!(recommended_threads(size), 1, "{size} bytes");
+    }
+}
+
+/// A thread per started MiB, as long as there are CPUs for them
+#[test]
+fn medium_files_get_a_thread_per_chunk() {
+    assert_eq!(recommended_threads(3 * MIB), cpus().min(3));
+    assert_eq!(recommended_threads(3 * MIB + 1), cpus().min(4));
+}
+
+#[test]
+fn huge_files_get_every_cpu() {
+    for size in [1 << 30, 1 << 40, u64::MAX] {
+        assert_eq!(recommended_threads(size), cpus(), "{size} bytes");
+    }
+}
+
+#[test]
+fn threads_never_drop_as_files_grow() {
+    let sizes = (0..64).map(|shift| 1u64 << shift);
+    let threads = sizes.map(recommended_threads).collect::<Vec<_>>();
+    assert!(threads.windows(2).all(|w| w[0] <= w[1]), "{threads:?}");
+}
+
+/// Hosts that hide their CPUs get a single worker
+#[test]
+fn unknown_parallelism_falls_back_to_one_cpu() {
+    let unknown = || Err(std::io::Error::from(std::io::ErrorKind::Unsupported));
+    assert_eq!(brc_rust::available_cpus_from(&unknown), 1);
+
+    let known = || Ok(std::num::NonZeroUsize::new(12).unwrap());
+    assert_eq!(brc_rust::available_cpus_from(&known), 12);
+}
+
+#[test]
+fn leave_cores_runs_on_the_remaining_cores() {
+    let cores = std::thread::available_parallelism().unwrap().get();
+    let lines = fs::read_to_string(fixture("measurements.txt"))
+        .unwrap()
+        .lines()
+        .count();
+
+    for leave in [0, 1, cores, cores + 1] {
+        let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+            .args(["--small-file-threshold", "0", "--dump-partials"])
+            .args(["--leave-cores", &leave.to_string()])
+            .arg(fixture("measurements.txt"))
+            .output()
+            .expect("failed to run brc-rust");
+        assert!(output.status.success());
+
+        let workers = String::from_utf8_lossy(&output.stderr)
+            .lines()
+            .filter(|line| line.starts_with("worker "))
+            .count();
+        let expected = cores.saturating_sub(leave).max(1).min(lines);
+        assert_eq!(workers, expected, "--leave-cores {leave}");
+    }
+}
+
+#[test]
+fn brc_threads_sets_the_worker_count() {
+    let golden = fs::read(fixture("measurements.golden")).unwrap();
+    let brc = |threads: &str, args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+            .env("BRC_THREADS", threads)
+            .args(["--small-file-threshold", "0", "--dump-partials"])
+            .args(args)
+            .arg(fixture("measurements.txt"))
+            .output()
+            .expect("failed to run brc-rust")
+    };
+    let workers = |output: &std::process::Output| {
+        String::from_utf8_lossy(&output.stderr)
+            .lines()
+            .filter(|line| line.starts_with("worker "))
+            .count()
+    };
+
+    for (threads, expected) in [("1", 1), ("8", 8)] {
+        let output = brc(threads, &[]);
+        assert!(output.status.success());
+        assert_eq!(workers(&output), expected);
+        assert_eq!(output.stdout, golden);
+    }
+
+    // --threads wins over the environment
+    assert_eq!(workers(&brc("8", &["--threads", "3"])), 3);
+
+    for threads in ["0", "5000", "many"] {
+        let output = brc(threads, &[]);
+        assert!(!output.status.success(), "BRC_THREADS={threads}");
+        assert!(String::from_utf8_lossy(&output.stderr).contains("BRC_THREADS"));
+    }
+}
+
+/// Below the threshold one thread does all the work, at 0 every requested
+/// worker runs, with the same result either way.
+#[test]
+fn small_file_fast_path_matches_threaded_path() {
+    let golden = fs::read(fixture("measurements.golden")).unwrap();
+
+    for (threshold, threads) in [("1048576", "threads: 1, "), ("0", "threads: 4, ")] {
+        let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+            .args(["--verbose", "--threads", "4"])
+            .args(["--small-file-threshold", threshold])
+            .arg(fixture("measurements.txt"))
+            .output()
+            .expect("failed to run brc-rust");
+        assert!(output.status.success(), "threshold {threshold}");
+
+        assert_eq!(output.stdout, golden, "threshold {threshold}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(threads), "threshold {threshold}: {stderr}");
+    }
+}
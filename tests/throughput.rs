@@ -0,0 +1,46 @@
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use brc_rust::throughput::{Clock, ThroughputGuard};
+
+const GB: u64 = 1_000_000_000;
+
+/// A clock that only moves when told to
+#[derive(Clone, Default)]
+struct FakeClock(Rc<Cell<Duration>>);
+
+impl FakeClock {
+    fn advance_to(&self, secs: u64) {
+        self.0.set(Duration::from_secs(secs));
+    }
+}
+
+impl Clock for FakeClock {
+    fn elapsed(&self) -> Duration {
+        self.0.get()
+    }
+}
+
+#[test]
+fn slow_processing_is_tolerated_during_warmup() {
+    let clock = FakeClock::default();
+    let guard = ThroughputGuard::new(clock.clone(), 1.0, Duration::from_secs(2));
+
+    for secs in [0, 1] {
+        clock.advance_to(secs);
+        assert_eq!(guard.check(0), Ok(()), "{secs}s");
+    }
+}
+
+#[test]
+fn low_throughput_after_warmup_aborts() {
+    let clock = FakeClock::default();
+    let guard = ThroughputGuard::new(clock.clone(), 1.0, Duration::from_secs(2));
+
+    clock.advance_to(4);
+    assert_eq!(guard.check(8 * GB), Ok(()));
+    assert_eq!(guard.check(4 * GB), Ok(()));
+    assert_eq!(
+        guard.check(2 * GB),
+        Err("throughput of 0.500 GB/s is below the minimum of 1 GB/s".to_string())
+    );
+}
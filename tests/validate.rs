@@ -0,0 +1,93 @@
+use std::{path::Path, process::Command};
+
+fn fixture(name: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn run(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(args)
+        .output()
+        .expect("failed to run brc-rust")
+}
+
+/// Two fractional digits, no fractional digit, no delimiter, four integer
+/// digits, no integer digit and trailing garbage, among well-formed lines
+#[test]
+fn malformed_values_are_skipped_and_counted() {
+    let path = fixture("malformed_values.txt");
+    for threads in ["1", "3"] {
+        let split = ["--small-file-threshold", "0", "--threads", threads];
+        let output = run(&[&["--validate", "--verbose"][..], &split, &[&path]].concat());
+        assert!(output.status.success(), "{threads} threads");
+
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "{Abha=-3.5/-1.2/1.0, Baku=-0.5/1.0/2.5}",
+            "{threads} threads"
+        );
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("lines: 4, malformed: 6, "),
+            "{threads} threads: {stderr}"
+        );
+    }
+}
+
+#[test]
+fn malformed_value_is_an_error_under_strict() {
+    let output = run(&["--strict", &fixture("malformed_values.txt")]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("malformed line at byte 9: Abha;12.34"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn well_formed_values_pass_validation() {
+    let golden = std::fs::read(fixture("measurements.golden")).unwrap();
+
+    for flag in ["--validate", "--strict"] {
+        let output = run(&[flag, &fixture("measurements.txt")]);
+        assert!(output.status.success(), "{flag}");
+        assert_eq!(output.stdout, golden, "{flag}");
+    }
+}
+
+#[test]
+fn invalid_utf8_name_is_rejected_under_strict_utf8() {
+    let path = std::env::temp_dir().join(format!("brc-strict-utf8-{}.txt", std::process::id()));
+    std::fs::write(&path, b"Abha;1.0\n\xff\xfeX;2.0\nBaku;3.0\n").unwrap();
+    let path = path.to_str().unwrap();
+
+    let lenient = run(&[path]);
+    let skipped = run(&["--strict-utf8", "--verbose", path]);
+    let rejected = run(&["--strict-utf8", "--strict", path]);
+    std::fs::remove_file(path).unwrap();
+
+    assert!(lenient.status.success());
+    assert_eq!(
+        lenient.stdout,
+        b"{Abha=1.0/1.0/1.0, Baku=3.0/3.0/3.0, \xff\xfeX=2.0/2.0/2.0}"
+    );
+
+    assert!(skipped.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&skipped.stdout),
+        "{Abha=1.0/1.0/1.0, Baku=3.0/3.0/3.0}"
+    );
+    let stderr = String::from_utf8_lossy(&skipped.stderr);
+    assert!(stderr.contains("lines: 2, malformed: 1, "), "{stderr}");
+
+    assert_eq!(rejected.status.code(), Some(1));
+    assert!(rejected.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&rejected.stderr);
+    assert!(stderr.contains("malformed line at byte 9: "), "{stderr}");
+}
@@ -0,0 +1,36 @@
+use std::{fs, path::Path, process::Command};
+
+#[test]
+fn verbose_reports_lines_and_stations_on_stderr() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let golden = fs::read(fixtures.join("measurements.golden")).unwrap();
+
+    for threads in ["1", "2"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+            .args([
+                "--verbose",
+                "--small-file-threshold",
+                "0",
+                "--threads",
+                threads,
+            ])
+            .arg(fixtures.join("measurements.txt"))
+            .output()
+            .expect("failed to run brc-rust");
+        assert!(output.status.success());
+
+        // The results are untouched
+        assert_eq!(output.stdout, golden);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        let summary = stderr
+            .lines()
+            .find(|line| line.starts_with("lines: "))
+            .unwrap_or_else(|| panic!("no summary in {stderr}"));
+        assert!(
+            summary.starts_with(&format!(
+                "lines: 204, malformed: 0, stations: 16, threads: {threads}, elapsed: "
+            )),
+            "{summary}"
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use std::{env, fs, process::Command};
+
+const LINES: u32 = 500_000;
+
+/// Runs the workers and the `--watch` reader concurrently over a generated
+/// file and checks the reader only ever sees the station's count grow.
+#[test]
+fn watch_reports_growing_count() {
+    let path = env::temp_dir().join(format!("brc-watch-{}.txt", std::process::id()));
+    let mut input = String::new();
+    for i in 0..LINES {
+        let station = if i % 4 == 0 { "Watched" } else { "Other" };
+        input += &format!("{station};{}.{}\n", i % 50, i % 10);
+    }
+    fs::write(&path, input).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(["--small-file-threshold", "0", "--watch", "Watched"])
+        .arg(&path)
+        .output()
+        .expect("failed to run brc-rust");
+    fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+
+    let counts = String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .filter_map(|line| line.strip_prefix("Watched: "))
+        .map(|line| line.split(' ').next().unwrap().parse::<u32>().unwrap())
+        .collect::<Vec<_>>();
+
+    assert!(counts.windows(2).all(|w| w[0] <= w[1]), "{counts:?}");
+    assert_eq!(counts.last(), Some(&(LINES / 4)));
+}
+
+#[test]
+fn watched_mean_is_rounded_like_the_output() {
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/half_tenths.txt"
+    );
+    let output = Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .args(["--rounding", "floor", "--watch", "E", path])
+        .output()
+        .expect("failed to run brc-rust");
+    assert!(output.status.success());
+
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "E: 2 records, -2.3/-2.3/-2.2\n"
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "{D=2.2/2.2/2.3, E=-2.3/-2.3/-2.2, F=0.1/0.1/0.2}"
+    );
+}
@@ -0,0 +1,72 @@
+use std::{fs, path::Path};
+
+fn write_text(data: &[u8], threads: usize) -> Vec<u8> {
+    let mut out = Vec::new();
+    brc_rust::write_text(data, threads, &mut out).unwrap();
+    out
+}
+
+#[test]
+fn written_bytes_match_golden_file() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let input = fs::read(fixtures.join("measurements.txt")).unwrap();
+    let golden = fs::read(fixtures.join("measurements.golden")).unwrap();
+
+    for threads in [1, 4] {
+        assert!(write_text(&input, threads) == golden, "{threads} threads");
+    }
+}
+
+/// Names sorted by bytes, means rounded half up and a mean of zero from
+/// negative and positive values
+#[test]
+fn written_bytes_are_exact() {
+    let input = "Zürich;-0.1\nAbha;0.1\nZürich;0.1\nAbha;-0.6\nÅre;-99.9\n";
+
+    assert_eq!(
+        String::from_utf8(write_text(input.as_bytes(), 1)).unwrap(),
+        "{Abha=-0.6/-0.2/0.1, Zürich=-0.1/0.0/0.1, Åre=-99.9/-99.9/-99.9}"
+    );
+    assert_eq!(write_text(b"", 1), b"{}");
+}
+
+fn run_stdout(name: &str, input: &str) -> Vec<u8> {
+    let path = std::env::temp_dir().join(format!("brc-writer-{}-{name}.txt", std::process::id()));
+    fs::write(&path, input).unwrap();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_brc-rust"))
+        .arg(&path)
+        .output()
+        .expect("failed to run brc-rust");
+    fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+
+    output.stdout
+}
+
+/// The buffered stdout of the command line carries the same bytes as the
+/// writer, for a handful of stations and for thousands of them
+#[test]
+fn stdout_matches_written_bytes() {
+    let tiny = "Hamburg;12.0\nBulawayo;8.9\nPalembang;38.8\nHamburg;34.2\n";
+    assert_eq!(
+        String::from_utf8(run_stdout("tiny", tiny)).unwrap(),
+        "{Bulawayo=8.9/8.9/8.9, Hamburg=12.0/23.1/34.2, Palembang=38.8/38.8/38.8}"
+    );
+
+    let many = (0..10_000)
+        .map(|i| format!("Station {i};{}.{}\n", i % 90, i % 10))
+        .collect::<String>();
+    assert!(run_stdout("many", &many) == write_text(many.as_bytes(), 1));
+}
+
+/// Names that aren't valid UTF-8 are written as the bytes they are
+#[test]
+fn invalid_utf8_names_are_written_as_is() {
+    for threads in [1, 2] {
+        assert_eq!(
+            write_text(b"x\xffy;1.0\nx\xffy;2.0\n", threads),
+            b"{x\xffy=1.0/1.5/2.0}",
+            "{threads} threads"
+        );
+    }
+}